@@ -0,0 +1,222 @@
+use config::ConfigError;
+use ds::Size;
+use error::SimulatorError;
+
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+extern crate serde_json;
+
+// What a policy knows when a segment falls back to level three: the bytes of its level three
+// frames at every quality, best first, the seconds of video they hold, the seconds of video
+// buffered ahead, the throughput of the last transfers in bytes per second and the quality chosen
+// for the last fallback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbrState<'a> {
+    pub segment: usize,
+    pub bytes: &'a [f64],
+    pub duration: f64,
+    pub buffer: f64,
+    pub throughput: Option<f64>,
+    pub last: Option<usize>,
+}
+
+impl<'a> AbrState<'a> {
+    // bytes per second of video of every quality
+    pub fn rates(&self) -> Vec<f64> {
+        self.bytes.iter().map(|b| if self.duration > 0.0 { b / self.duration } else { 0.0 }).collect()
+    }
+
+    fn lowest(&self) -> usize {
+        self.bytes.len() - 1
+    }
+
+    // the best quality streamed at no more than `rate` bytes per second, else the lowest
+    fn best_within(&self, rate: f64) -> usize {
+        self.rates().iter().position(|&r| r <= rate).unwrap_or_else(|| self.lowest())
+    }
+}
+
+// Chooses the quality of the level three frames of a segment, as an index into `state.bytes`.
+pub trait AbrPolicy: fmt::Debug + Send + Sync {
+    fn choose(&self, state: &AbrState) -> usize;
+}
+
+// Always the same quality.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct FixedQuality {
+    pub quality: usize,
+}
+
+impl AbrPolicy for FixedQuality {
+    fn choose(&self, state: &AbrState) -> usize {
+        self.quality.min(state.lowest())
+    }
+}
+
+// Buffer based (BBA-0): the lowest quality below `reservoir` seconds of buffer, the best above
+// `reservoir + cushion`, and the rate growing linearly with the buffer in between.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct BufferBased {
+    pub reservoir: f64,
+    pub cushion: f64,
+}
+
+impl AbrPolicy for BufferBased {
+    fn choose(&self, state: &AbrState) -> usize {
+        let rates = state.rates();
+        let (best, lowest) = (rates[0], rates[rates.len() - 1]);
+        let filled = ((state.buffer - self.reservoir) / self.cushion).clamp(0.0, 1.0);
+        state.best_within(lowest + (best - lowest) * filled)
+    }
+}
+
+// Throughput based: the best quality within `safety` times the throughput of the last transfers,
+// the lowest before any.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct ThroughputBased {
+    pub safety: f64,
+}
+
+impl AbrPolicy for ThroughputBased {
+    fn choose(&self, state: &AbrState) -> usize {
+        match state.throughput {
+            Some(throughput) => state.best_within(self.safety * throughput),
+            None => state.lowest(),
+        }
+    }
+}
+
+fn default_rebuffer_penalty() -> f64 {
+    4.3
+}
+
+fn default_switch_penalty() -> f64 {
+    1.0
+}
+
+// Model predictive control: plays every sequence of qualities over the next `horizon` fallbacks,
+// taken as large as this one, at the last throughput, and keeps the first quality of the sequence
+// with the best quality of experience: the bitrates in Mbit/s, less `rebuffer_penalty` per second
+// stalled and `switch_penalty` per Mbit/s switched.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct Mpc {
+    pub horizon: usize,
+    #[serde(default = "default_rebuffer_penalty")]
+    pub rebuffer_penalty: f64,
+    #[serde(default = "default_switch_penalty")]
+    pub switch_penalty: f64,
+}
+
+impl Mpc {
+    fn experience(&self, state: &AbrState, throughput: f64, plan: &[usize]) -> f64 {
+        let mbps: Vec<f64> = state.rates().iter().map(|r| r * 8.0 / 1e6).collect();
+        let mut buffer = state.buffer;
+        let mut last = state.last.map(|q| mbps[q.min(state.lowest())]);
+        let mut experience = 0.0;
+        for &quality in plan {
+            let download = state.bytes[quality] / throughput;
+            experience += mbps[quality] - self.rebuffer_penalty * (download - buffer).max(0.0);
+            if let Some(last) = last {
+                experience -= self.switch_penalty * (mbps[quality] - last).abs();
+            }
+            buffer = (buffer - download).max(0.0) + state.duration;
+            last = Some(mbps[quality]);
+        }
+        experience
+    }
+}
+
+impl AbrPolicy for Mpc {
+    fn choose(&self, state: &AbrState) -> usize {
+        let throughput = match state.throughput {
+            Some(throughput) if throughput > 0.0 => throughput,
+            _ => return state.lowest(),
+        };
+        let qualities = state.bytes.len();
+        let plans = qualities.pow(self.horizon as u32);
+        let mut best = (f64::NEG_INFINITY, state.lowest());
+        let mut plan = vec![0; self.horizon];
+        for mut index in 0..plans {
+            for quality in plan.iter_mut() {
+                *quality = index % qualities;
+                index /= qualities;
+            }
+            let experience = self.experience(state, throughput, &plan);
+            if experience > best.0 {
+                best = (experience, plan[0]);
+            }
+        }
+        best.1
+    }
+}
+
+// A policy as written in json, tagged by its `type`.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AbrPolicyConfig {
+    Fixed(FixedQuality),
+    Buffer(BufferBased),
+    Throughput(ThroughputBased),
+    Mpc(Mpc),
+}
+
+// most sequences the model predictive control plays for one segment
+const MPC_MAX_PLANS: usize = 100_000;
+
+impl AbrPolicyConfig {
+    pub fn policy(&self, qualities: usize) -> Result<Arc<dyn AbrPolicy>, ConfigError> {
+        let invalid = |reason: String| Err(ConfigError::InvalidAbr(reason));
+        match *self {
+            AbrPolicyConfig::Fixed(fixed) if fixed.quality >= qualities =>
+                invalid(format!("quality {} of {} qualities", fixed.quality, qualities)),
+            AbrPolicyConfig::Buffer(bba) if !(bba.reservoir >= 0.0 && bba.cushion > 0.0 && bba.cushion.is_finite()) =>
+                invalid(format!("reservoir {} and cushion {}", bba.reservoir, bba.cushion)),
+            AbrPolicyConfig::Throughput(rate) if !(rate.safety > 0.0 && rate.safety.is_finite()) =>
+                invalid(format!("safety {} is not positive", rate.safety)),
+            AbrPolicyConfig::Mpc(mpc) if mpc.horizon == 0 || qualities.checked_pow(mpc.horizon as u32).is_none_or(|n| n > MPC_MAX_PLANS) =>
+                invalid(format!("horizon {} over {} qualities", mpc.horizon, qualities)),
+            AbrPolicyConfig::Fixed(fixed) => Ok(Arc::new(fixed)),
+            AbrPolicyConfig::Buffer(bba) => Ok(Arc::new(bba)),
+            AbrPolicyConfig::Throughput(rate) => Ok(Arc::new(rate)),
+            AbrPolicyConfig::Mpc(mpc) => Ok(Arc::new(mpc)),
+        }
+    }
+}
+
+// The qualities of the level three frames, best first, and the policy choosing between them.
+// Written in json like
+// `{"qualities": ["3840x2160", "2560x1440", "1920x1080"], "policy": {"type": "buffer", "reservoir": 2, "cushion": 6}}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AbrModel {
+    pub qualities: Vec<Size>,
+    pub policy: AbrPolicyConfig,
+}
+
+impl AbrModel {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SimulatorError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+        serde_json::from_reader(file).map_err(|e| SimulatorError::json(&name, e))
+    }
+
+    pub fn abr(&self) -> Result<Abr, ConfigError> {
+        Ok(Abr { qualities: self.qualities.clone(), policy: self.policy.policy(self.qualities.len())? })
+    }
+}
+
+// The level three qualities, best first, with the policy a simulation runs.
+#[derive(Debug, Clone)]
+pub struct Abr {
+    pub qualities: Vec<Size>,
+    pub policy: Arc<dyn AbrPolicy>,
+}
+
+impl Abr {
+    // the quality the policy chooses, within the qualities
+    pub fn choose(&self, state: &AbrState) -> usize {
+        self.policy.choose(state).min(state.bytes.len() - 1)
+    }
+}
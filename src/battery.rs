@@ -0,0 +1,196 @@
+use config::ConfigError;
+use error::SimulatorError;
+use output::Row;
+use report::{SimulationParameters, SummaryReport};
+
+use std::f64;
+use std::fs::File;
+use std::path::Path;
+
+extern crate serde_json;
+
+// share of the charge the discharge is integrated over in one step
+const CHARGE_STEP: f64 = 0.001;
+
+// Share of the drawn energy that reaches the device at a state of charge, in (0, 1].
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct DischargePoint {
+    pub charge: f64,
+    pub efficiency: f64,
+}
+
+fn default_cutoff() -> f64 {
+    0.0
+}
+
+// A battery of `capacity` watt hours. Playback stops at the `cutoff` state of charge. Without a
+// `curve` every watt hour reaches the device; a curve makes the discharge nonlinear, its
+// efficiency is interpolated between the points and the end points hold outside of them.
+// Written in json like
+// `{"capacity": 15.4, "cutoff": 0.05, "curve": [{"charge": 1.0, "efficiency": 1.0}, {"charge": 0.2, "efficiency": 0.85}]}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Battery {
+    pub capacity: f64,
+    #[serde(default = "default_cutoff")]
+    pub cutoff: f64,
+    #[serde(default)]
+    pub curve: Vec<DischargePoint>,
+}
+
+impl Battery {
+    pub fn new(capacity: f64, cutoff: f64, curve: Vec<DischargePoint>) -> Result<Self, ConfigError> {
+        Battery { capacity, cutoff, curve }.checked()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SimulatorError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+        let battery: Battery = serde_json::from_reader(file).map_err(|e| SimulatorError::json(&name, e))?;
+        Ok(battery.checked()?)
+    }
+
+    // the curve sorted by charge, once the battery is known to be valid
+    pub(crate) fn checked(mut self) -> Result<Self, ConfigError> {
+        if !(self.capacity > 0.0 && self.capacity.is_finite()) {
+            return Err(ConfigError::InvalidBattery(format!("capacity {} is not positive", self.capacity)));
+        }
+        if !(0.0..1.0).contains(&self.cutoff) {
+            return Err(ConfigError::InvalidBattery(format!("cutoff {} is not within [0, 1)", self.cutoff)));
+        }
+        if let Some(p) = self.curve.iter().find(|p| !((0.0..=1.0).contains(&p.charge) && p.efficiency > 0.0 && p.efficiency <= 1.0)) {
+            return Err(ConfigError::InvalidBattery(format!("point at charge {} with efficiency {}", p.charge, p.efficiency)));
+        }
+        self.curve.sort_by(|a, b| a.charge.total_cmp(&b.charge));
+        if self.curve.windows(2).any(|w| w[0].charge == w[1].charge) {
+            return Err(ConfigError::InvalidBattery("two points at the same charge".to_string()));
+        }
+        Ok(self)
+    }
+
+    pub fn efficiency(&self, charge: f64) -> f64 {
+        let points = &self.curve;
+        if points.is_empty() {
+            return 1.0;
+        }
+        match points.iter().position(|p| p.charge > charge) {
+            Some(0) => points[0].efficiency,
+            Some(i) => {
+                let (low, high) = (points[i - 1], points[i]);
+                low.efficiency + (high.efficiency - low.efficiency) * (charge - low.charge) / (high.charge - low.charge)
+            }
+            None => points[points.len() - 1].efficiency,
+        }
+    }
+
+    // Discharge from full to the cutoff while the device draws `watts`. The time to draw each
+    // step of charge follows the efficiency in the middle of the step.
+    pub fn discharge(&self, watts: f64) -> Discharge {
+        let mut points = vec![ChargePoint { time: 0.0, charge: 1.0 }];
+        // nothing drawn, or no users to draw it
+        if !(watts > 0.0 && watts.is_finite()) {
+            points.push(ChargePoint { time: f64::INFINITY, charge: self.cutoff });
+            return Discharge { points };
+        }
+        let joules = self.capacity * 3600.0;
+        let mut charge = 1.0;
+        let mut time = 0.0;
+        while charge > self.cutoff {
+            let step = CHARGE_STEP.min(charge - self.cutoff);
+            time += step * joules * self.efficiency(charge - step / 2.0) / watts;
+            charge -= step;
+            points.push(ChargePoint { time, charge });
+        }
+        Discharge { points }
+    }
+}
+
+// State of charge, in [0, 1], `time` seconds into the playback.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub struct ChargePoint {
+    pub time: f64,
+    pub charge: f64,
+}
+
+// State of charge over the playback, from full down to the cutoff of the battery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discharge {
+    points: Vec<ChargePoint>,
+}
+
+impl Discharge {
+    pub fn points(&self) -> &[ChargePoint] {
+        &self.points
+    }
+
+    // seconds of playback until the cutoff, infinite when nothing is drawn
+    pub fn playback_time(&self) -> f64 {
+        self.points[self.points.len() - 1].time
+    }
+
+    // state of charge `time` seconds into the playback, the cutoff past its end
+    pub fn charge_at(&self, time: f64) -> f64 {
+        match self.points.iter().position(|p| p.time > time) {
+            Some(0) => 1.0,
+            Some(i) => {
+                let (low, high) = (self.points[i - 1], self.points[i]);
+                if high.time.is_infinite() {
+                    return low.charge;
+                }
+                low.charge + (high.charge - low.charge) * (time - low.time) / (high.time - low.time)
+            }
+            None => self.points[self.points.len() - 1].charge,
+        }
+    }
+}
+
+// The state of charge of one summary at one minute of playback, with the projected playback time
+// and the charge left after the simulated session. Every summary gives one row per minute until
+// the battery reaches its cutoff.
+#[derive(Debug, Serialize)]
+pub struct BatteryRow<'a> {
+    pub video: &'a str,
+    pub users: usize,
+    pub parameters: &'a SimulationParameters,
+    // mean power of the device over the users
+    pub watts: f64,
+    pub playback_minutes: f64,
+    // state of charge at the end of the simulated video
+    pub session_charge: f64,
+    pub minute: usize,
+    pub charge: f64,
+}
+
+impl<'a> BatteryRow<'a> {
+    pub fn rows(summary: &'a SummaryReport, battery: &Battery) -> Vec<BatteryRow<'a>> {
+        let energy = &summary.energy;
+        let watts = energy.wifi_watts + energy.soc_watts + energy.display_watts;
+        let discharge = battery.discharge(watts);
+        let playback_time = discharge.playback_time();
+        let minutes = if playback_time.is_finite() { (playback_time / 60.0).ceil() as usize } else { 0 };
+        (0..minutes + 1).map(|minute| BatteryRow {
+            video: &summary.video,
+            users: summary.users,
+            parameters: &summary.parameters,
+            watts,
+            playback_minutes: playback_time / 60.0,
+            session_charge: discharge.charge_at(energy.duration),
+            minute,
+            charge: discharge.charge_at(minute as f64 * 60.0),
+        }).collect()
+    }
+}
+
+impl<'a> Row for BatteryRow<'a> {
+    fn columns() -> &'static [&'static str] {
+        &["video", "users", "threshold", "segment", "l1", "l2", "opt", "watts", "playback_min", "session_charge",
+          "minute", "charge"]
+    }
+
+    fn values(&self) -> Vec<String> {
+        let p = self.parameters;
+        vec![self.video.to_string(), self.users.to_string(), p.threshold.to_string(), p.segment.to_string(),
+             p.level_one.to_string(), p.level_two.to_string(), p.opt.to_string(), self.watts.to_string(),
+             self.playback_minutes.to_string(), self.session_charge.to_string(), self.minute.to_string(),
+             self.charge.to_string()]
+    }
+}
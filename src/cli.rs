@@ -0,0 +1,393 @@
+use config::ParameterGrid;
+use output::Format;
+use power::PowerSource;
+
+use std::error::Error;
+use std::fmt;
+use std::num::ParseFloatError;
+use std::str::{FromStr, ParseBoolError};
+
+pub const USAGE: &str = "\
+Usage:
+    simulator simulate <power|hit> [OPTIONS]
+    simulator compare [OPTIONS]
+    simulator sweep [OPTIONS]
+    simulator run <MANIFEST>
+    simulator fit --log <FILE>... --labels <FILE> [--output <FILE>]
+    simulator help
+
+Commands:
+    simulate power    average wifi/soc power, hit ratios and segment hit rate over all users
+    simulate hit      average hit ratio of each cache level over all users
+    compare           per-user hit ratios of L1+L2, L1 only and L1+L2 with O1
+    sweep             `simulate power` for every combination of the listed parameter values
+    run               every video and parameter combination of a json manifest, see auto_pc.json
+    fit               power constants from measurement logs of `timestamp,rail,mW` lines and a label file of
+                      `name,rail,start,end` windows, in the format of power_1080p.json; --log can be repeated
+
+Options:
+    --video <NAME>           name of the video in the power constants, e.g. Paris (required)
+    --users <DIR>            directory of user viewport files (required)
+    --tracedump <FILE>       object tracedump of the video (required)
+    --cluster <FILE>         object cluster json of the video (required)
+    --fps <F>                frame rate of the video, for the energy in joules [default: 30]
+    --threshold <F>          minimum cover ratio for a cache hit, in [0, 1] [default: 0.96]
+    --segment <N>            frames per segment [default: 20]
+    --l1 <WxH>               level one viewport size [default: 1440x1440]
+    --l2 <WxH>               level two viewport size, same as --l1 disables level two
+                             [default: same as --l1]
+    --opt <O0|O1>            O1 predicts level two misses from the client sensor [default: O0]
+    --power-360 <FILE>       power constants of the 4k 360 video [default: power_4k_360.json]
+    --power-1080p <FILE>     power constants of the 1080p video [default: power_1080p.json]
+    --power-profiles <FILE>  json bundling the power constants of several devices, keyed by device
+                             name, used instead of --power-360 and --power-1080p
+    --device <NAME>          device to pick from --power-profiles
+    --format <FORMAT>        plain, csv, jsonl or pretty; all but plain name every column and parameter
+                             [default: plain]
+    --per-user <FILE>        also write the report of every user to FILE, in the same format
+    --timeline <FILE>        also write the power of every frame of every user to FILE, in the same format
+    --jobs <N>               worker threads running the users and parameter points in parallel
+                             [default: number of cpus]
+    --soc-scaling <MODEL>    how the SoC power of a 1280x720 tile scales with the level one and two sizes:
+                             constant, linear or a json file with a curve or table [default: constant]
+    --l2-reprojection <true|false>
+                             whether level two frames pay for reprojection [default: true]
+    --display <FILE>         json model of the display panel whose power is added to every frame, see README
+    --network <FILE>         json model of the network; segments are sent over it and the radio power replaces
+                             the wifi constants, see README
+    --ladder <FILE>          json bitrate ladders sizing the frames of every level in bytes, see README
+    --abr <FILE>             json qualities of level three and the policy choosing one per segment, see README
+    --prefetch <FILE>        json level fetched ahead with segments predicted below a confidence, see README
+    --bandwidth-trace <FILE> replay the throughput of FILE instead of the bandwidth of the network, in lines of
+                             `time_ms bytes` or mahimahi `time_ms`; requires --network
+    --battery <FILE>         json model of the battery, see README
+    --battery-output <FILE>  write the projected playback time and state of charge per minute to FILE,
+                             in the same format; requires --battery
+    -h, --help               print this message
+
+`sweep` accepts comma separated lists for --threshold, --segment, --l1, --l2 and --opt, e.g.
+    simulator sweep --video Paris --users user_viewport_result/Paris-sJxiPiAaB4k --tracedump tracedump/paris.txt \\
+        --cluster object_as_cluster/paris.json --threshold 0.8,0.9,1.0 --l1 1200x1200,1440x1440";
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Power,
+    Hit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    // name of the video in the power constants
+    pub video: String,
+    pub users: String,
+    pub tracedump: String,
+    pub cluster: String,
+    pub fps: Option<f64>,
+    pub power: PowerSource,
+    pub grid: ParameterGrid,
+    pub soc_scaling: Option<String>,
+    pub reproject_level_two: Option<bool>,
+    // json file of the display model
+    pub display: Option<String>,
+    // json file of the network model
+    pub network: Option<String>,
+    // json file of the bitrate ladders
+    pub ladder: Option<String>,
+    // json file of the abr policy
+    pub abr: Option<String>,
+    // json file of the prefetch level and confidence
+    pub prefetch: Option<String>,
+    // throughput trace replacing the bandwidth of the network model
+    pub bandwidth_trace: Option<String>,
+    // json file of the battery model and the file its discharge is written to
+    pub battery: Option<String>,
+    pub battery_output: Option<String>,
+    pub format: Format,
+    pub per_user: Option<String>,
+    pub timeline: Option<String>,
+    pub jobs: Option<usize>,
+}
+
+// Arguments of `fit`: the measurement logs, the label file naming their windows and where the
+// power constants go, stdout by default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitOptions {
+    pub logs: Vec<String>,
+    pub labels: String,
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Simulate(Mode, Options),
+    Compare(Options),
+    Sweep(Options),
+    Run(String),
+    Fit(FitOptions),
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    MissingCommand,
+    UnknownCommand(String),
+    UnknownArgument(String),
+    MissingArgument(&'static str),
+    MissingValue(String),
+    InvalidValue { argument: String, value: String, reason: String },
+    NotAList { argument: String, command: &'static str },
+    Conflict(&'static str, &'static str),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CliError::MissingCommand => write!(f, "missing command"),
+            CliError::UnknownCommand(ref command) => write!(f, "unknown command `{}`", command),
+            CliError::UnknownArgument(ref argument) => write!(f, "unknown argument `{}`", argument),
+            CliError::MissingArgument(argument) => write!(f, "missing required argument `{}`", argument),
+            CliError::MissingValue(ref argument) => write!(f, "`{}` requires a value", argument),
+            CliError::InvalidValue { ref argument, ref value, ref reason } =>
+                write!(f, "invalid value `{}` for `{}`: {}", value, argument, reason),
+            CliError::NotAList { ref argument, command } =>
+                write!(f, "`{}` takes a single value for `{}`, use `sweep` to run a list", argument, command),
+            CliError::Conflict(first, second) => write!(f, "`{}` cannot be used with `{}`", first, second),
+        }
+    }
+}
+
+impl Error for CliError {}
+
+fn parse_list<T: FromStr>(argument: &str, value: &str) -> Result<Vec<T>, CliError>
+    where T::Err: fmt::Display {
+    value.split(',')
+        .map(|v| v.trim().parse::<T>().map_err(|e| CliError::InvalidValue {
+            argument: argument.to_string(),
+            value: v.to_string(),
+            reason: e.to_string(),
+        }))
+        .collect()
+}
+
+fn parse_jobs(argument: &str, value: &str) -> Result<usize, CliError> {
+    let invalid = |reason: &str| CliError::InvalidValue {
+        argument: argument.to_string(),
+        value: value.to_string(),
+        reason: reason.to_string(),
+    };
+    match value.parse::<usize>() {
+        Ok(0) => Err(invalid("at least one worker is needed")),
+        Ok(jobs) => Ok(jobs),
+        Err(e) => Err(invalid(&e.to_string())),
+    }
+}
+
+// the next argument and its value, accepting both `--name value` and `--name=value`
+fn next_argument<'a, I: Iterator<Item = &'a String>>(iter: &mut I) -> Result<Option<(String, String)>, CliError> {
+    let arg = match iter.next() {
+        Some(arg) => arg,
+        None => return Ok(None),
+    };
+    let (name, inline_value) = match arg.find('=') {
+        Some(pos) if arg.starts_with("--") => (&arg[..pos], Some(arg[pos + 1..].to_string())),
+        _ => (arg.as_str(), None),
+    };
+    if !name.starts_with("--") {
+        return Err(CliError::UnknownArgument(arg.clone()));
+    }
+    let value = match inline_value {
+        Some(value) => value,
+        None => iter.next().cloned().ok_or_else(|| CliError::MissingValue(name.to_string()))?,
+    };
+    Ok(Some((name.to_string(), value)))
+}
+
+fn parse_options(args: &[String], command: &'static str) -> Result<Options, CliError> {
+    let mut video = None;
+    let mut users = None;
+    let mut tracedump = None;
+    let mut cluster = None;
+    let mut fps = None;
+    let mut power_360 = None;
+    let mut power_1080p = None;
+    let mut power_profiles = None;
+    let mut device = None;
+    let mut grid = ParameterGrid::default();
+    let mut soc_scaling = None;
+    let mut reproject_level_two = None;
+    let mut display = None;
+    let mut network = None;
+    let mut bandwidth_trace = None;
+    let mut ladder = None;
+    let mut abr = None;
+    let mut prefetch = None;
+    let mut battery = None;
+    let mut battery_output = None;
+    let mut format = Format::default();
+    let mut per_user = None;
+    let mut timeline = None;
+    let mut jobs = None;
+
+    let mut iter = args.iter();
+    while let Some((name, value)) = next_argument(&mut iter)? {
+        let name = name.as_str();
+        match name {
+            "--video" => video = Some(value),
+            "--users" => users = Some(value),
+            "--tracedump" => tracedump = Some(value),
+            "--cluster" => cluster = Some(value),
+            "--fps" => fps = Some(value.parse().map_err(|e: ParseFloatError| CliError::InvalidValue {
+                argument: name.to_string(),
+                value: value.clone(),
+                reason: e.to_string(),
+            })?),
+            "--power-360" => power_360 = Some(value),
+            "--power-1080p" => power_1080p = Some(value),
+            "--power-profiles" => power_profiles = Some(value),
+            "--device" => device = Some(value),
+            "--threshold" => grid.thresholds = parse_list(name, &value)?,
+            "--segment" => grid.segments = parse_list(name, &value)?,
+            "--l1" => grid.level_ones = parse_list(name, &value)?,
+            "--l2" => grid.level_twos = parse_list(name, &value)?,
+            "--opt" => grid.opts = parse_list(name, &value)?,
+            "--soc-scaling" => soc_scaling = Some(value),
+            "--l2-reprojection" => reproject_level_two = Some(value.parse().map_err(|e: ParseBoolError| CliError::InvalidValue {
+                argument: name.to_string(),
+                value: value.clone(),
+                reason: e.to_string(),
+            })?),
+            "--display" => display = Some(value),
+            "--network" => network = Some(value),
+            "--bandwidth-trace" => bandwidth_trace = Some(value),
+            "--ladder" => ladder = Some(value),
+            "--abr" => abr = Some(value),
+            "--prefetch" => prefetch = Some(value),
+            "--battery" => battery = Some(value),
+            "--battery-output" => battery_output = Some(value),
+            "--format" => format = value.parse().map_err(|e| CliError::InvalidValue {
+                argument: name.to_string(),
+                value: value.clone(),
+                reason: e,
+            })?,
+            "--per-user" => per_user = Some(value),
+            "--timeline" => timeline = Some(value),
+            "--jobs" => jobs = Some(parse_jobs(name, &value)?),
+            _ => return Err(CliError::UnknownArgument(name.to_string())),
+        }
+    }
+
+    if command != "sweep" {
+        let lists = [("--threshold", grid.thresholds.len()), ("--segment", grid.segments.len()),
+            ("--l1", grid.level_ones.len()), ("--l2", grid.level_twos.len()), ("--opt", grid.opts.len())];
+        if let Some(&(argument, _)) = lists.iter().find(|&&(_, n)| n > 1) {
+            return Err(CliError::NotAList { argument: argument.to_string(), command });
+        }
+    }
+
+    let power = match (power_profiles, device) {
+        (Some(path), Some(device)) => {
+            if power_360.is_some() {
+                return Err(CliError::Conflict("--power-profiles", "--power-360"));
+            }
+            if power_1080p.is_some() {
+                return Err(CliError::Conflict("--power-profiles", "--power-1080p"));
+            }
+            PowerSource::Bundle { path, device }
+        }
+        (Some(_), None) => return Err(CliError::MissingArgument("--device")),
+        (None, Some(_)) => return Err(CliError::MissingArgument("--power-profiles")),
+        (None, None) => match PowerSource::default() {
+            PowerSource::Files { power_360: default_360, power_1080p: default_1080p } => PowerSource::Files {
+                power_360: power_360.unwrap_or(default_360),
+                power_1080p: power_1080p.unwrap_or(default_1080p),
+            },
+            bundle => bundle,
+        },
+    };
+
+    match (&battery, &battery_output) {
+        (Some(_), None) => return Err(CliError::MissingArgument("--battery-output")),
+        (None, Some(_)) => return Err(CliError::MissingArgument("--battery")),
+        _ => {}
+    }
+    if bandwidth_trace.is_some() && network.is_none() {
+        return Err(CliError::MissingArgument("--network"));
+    }
+
+    Ok(Options {
+        video: video.ok_or(CliError::MissingArgument("--video"))?,
+        users: users.ok_or(CliError::MissingArgument("--users"))?,
+        tracedump: tracedump.ok_or(CliError::MissingArgument("--tracedump"))?,
+        cluster: cluster.ok_or(CliError::MissingArgument("--cluster"))?,
+        fps,
+        power,
+        grid,
+        soc_scaling,
+        reproject_level_two,
+        display,
+        network,
+        bandwidth_trace,
+        ladder,
+        abr,
+        prefetch,
+        battery,
+        battery_output,
+        format,
+        per_user,
+        timeline,
+        jobs,
+    })
+}
+
+fn parse_fit_options(args: &[String]) -> Result<FitOptions, CliError> {
+    let mut logs = vec![];
+    let mut labels = None;
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some((name, value)) = next_argument(&mut iter)? {
+        match name.as_str() {
+            "--log" => logs.push(value),
+            "--labels" => labels = Some(value),
+            "--output" => output = Some(value),
+            _ => return Err(CliError::UnknownArgument(name)),
+        }
+    }
+    if logs.is_empty() {
+        return Err(CliError::MissingArgument("--log"));
+    }
+    Ok(FitOptions {
+        logs,
+        labels: labels.ok_or(CliError::MissingArgument("--labels"))?,
+        output,
+    })
+}
+
+// parse the arguments after the program name
+pub fn parse(args: &[String]) -> Result<Command, CliError> {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        return Ok(Command::Help);
+    }
+    let command = args.first().ok_or(CliError::MissingCommand)?;
+    match command.as_ref() {
+        "simulate" => {
+            let mode = match args.get(1).map(|m| m.as_ref()) {
+                Some("power") => Mode::Power,
+                Some("hit") => Mode::Hit,
+                Some(other) => return Err(CliError::UnknownCommand(format!("simulate {}", other))),
+                None => return Err(CliError::MissingArgument("power|hit")),
+            };
+            Ok(Command::Simulate(mode, parse_options(&args[2..], "simulate")?))
+        }
+        "compare" => Ok(Command::Compare(parse_options(&args[1..], "compare")?)),
+        "sweep" => Ok(Command::Sweep(parse_options(&args[1..], "sweep")?)),
+        "run" => match args.get(1..) {
+            Some([manifest]) => Ok(Command::Run(manifest.clone())),
+            Some([]) | None => Err(CliError::MissingArgument("<MANIFEST>")),
+            Some(rest) => Err(CliError::UnknownArgument(rest[1].clone())),
+        },
+        "fit" => Ok(Command::Fit(parse_fit_options(&args[1..])?)),
+        "help" => Ok(Command::Help),
+        other => Err(CliError::UnknownCommand(other.to_string())),
+    }
+}
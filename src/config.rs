@@ -0,0 +1,409 @@
+use ds::Size;
+use abr::Abr;
+use ladder::BitrateLadder;
+use network::NetworkModel;
+use prefetch::{Prefetch, PrefetchLevel};
+use power::{Brightness, DisplayModel, PowerProfile, SocScaling};
+use constants;
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OptimizeVersion {
+    O0,
+    O1,
+}
+
+impl FromStr for OptimizeVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "O0" => Ok(OptimizeVersion::O0),
+            "O1" => Ok(OptimizeVersion::O1),
+            _ => Err(format!("expected O0 or O1, got `{}`", s)),
+        }
+    }
+}
+
+impl Serialize for OptimizeVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for OptimizeVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+impl fmt::Display for OptimizeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OptimizeVersion::O0 => write!(f, "O0"),
+            OptimizeVersion::O1 => write!(f, "O1"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    ThresholdOutOfRange(f64),
+    ZeroSegment,
+    LevelTwoSmallerThanLevelOne { level_one: Size, level_two: Size },
+    LargerThanPanorama { level: &'static str, size: Size },
+    InvalidSocScaling(String),
+    InvalidFrameRate(f64),
+    InvalidDisplay(String),
+    InvalidBattery(String),
+    InvalidNetwork(String),
+    InvalidLadder(String),
+    InvalidAbr(String),
+    InvalidPrefetch(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::ThresholdOutOfRange(threshold) =>
+                write!(f, "threshold {} is not within [0, 1]", threshold),
+            ConfigError::ZeroSegment => write!(f, "segment size must be greater than 0"),
+            ConfigError::LevelTwoSmallerThanLevelOne { level_one, level_two } =>
+                write!(f, "level two {} is smaller than level one {}", level_two, level_one),
+            ConfigError::LargerThanPanorama { level, size } =>
+                write!(f, "{} {} is larger than the panorama {}x{}", level, size,
+                       constants::FULL_SIZE_WIDTH_USIZE, constants::FULL_SIZE_HEIGHT_USIZE),
+            ConfigError::InvalidSocScaling(ref reason) => write!(f, "invalid soc scaling: {}", reason),
+            ConfigError::InvalidFrameRate(fps) => write!(f, "frame rate {} is not a positive number", fps),
+            ConfigError::InvalidDisplay(ref reason) => write!(f, "invalid display: {}", reason),
+            ConfigError::InvalidBattery(ref reason) => write!(f, "invalid battery: {}", reason),
+            ConfigError::InvalidNetwork(ref reason) => write!(f, "invalid network: {}", reason),
+            ConfigError::InvalidLadder(ref reason) => write!(f, "invalid bitrate ladder: {}", reason),
+            ConfigError::InvalidAbr(ref reason) => write!(f, "invalid abr: {}", reason),
+            ConfigError::InvalidPrefetch(ref reason) => write!(f, "invalid prefetch: {}", reason),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+// Parameters of a single simulation run. Only constructible through `SimulatorConfigBuilder` so
+// every config handed to the simulator has passed `validate`.
+#[derive(Debug, Clone)]
+pub struct SimulatorConfig {
+    pub(crate) threshold: f64,
+    pub(crate) segment: usize,
+    pub(crate) level_one: Size,
+    pub(crate) level_two: Size,
+    pub(crate) power: PowerProfile,
+    pub(crate) soc_scaling: SocScaling,
+    pub(crate) reproject_level_two: bool,
+    pub(crate) display: Option<DisplayModel>,
+    pub(crate) network: Option<NetworkModel>,
+    pub(crate) ladder: Option<BitrateLadder>,
+    pub(crate) abr: Option<Abr>,
+    pub(crate) prefetch: Option<Prefetch>,
+    pub(crate) opt: OptimizeVersion,
+}
+
+impl SimulatorConfig {
+    pub fn builder() -> SimulatorConfigBuilder {
+        SimulatorConfigBuilder::default()
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !(0.0..=1.0).contains(&self.threshold) {
+            return Err(ConfigError::ThresholdOutOfRange(self.threshold));
+        }
+        if self.segment == 0 {
+            return Err(ConfigError::ZeroSegment);
+        }
+        for &(level, size) in &[("level one", self.level_one), ("level two", self.level_two)] {
+            if size.width > constants::FULL_SIZE_WIDTH_USIZE || size.height > constants::FULL_SIZE_HEIGHT_USIZE {
+                return Err(ConfigError::LargerThanPanorama { level, size });
+            }
+        }
+        if self.level_two.width < self.level_one.width || self.level_two.height < self.level_one.height {
+            return Err(ConfigError::LevelTwoSmallerThanLevelOne {
+                level_one: self.level_one,
+                level_two: self.level_two,
+            });
+        }
+        if let Some(points) = self.soc_scaling.points() {
+            if points.is_empty() {
+                return Err(ConfigError::InvalidSocScaling("no points".to_string()));
+            }
+            if let Some(p) = points.iter().find(|p| !(p.pixels >= 0.0 && p.factor >= 0.0)) {
+                return Err(ConfigError::InvalidSocScaling(format!("negative point {} pixels, factor {}", p.pixels, p.factor)));
+            }
+            if points.windows(2).any(|w| w[0].pixels == w[1].pixels) {
+                return Err(ConfigError::InvalidSocScaling("two points with the same pixel count".to_string()));
+            }
+        }
+        if let Some(ref display) = self.display {
+            if !(display.refresh_rate > 0.0 && display.refresh_rate.is_finite()) {
+                return Err(ConfigError::InvalidDisplay(format!("refresh rate {} is not positive", display.refresh_rate)));
+            }
+            if !(display.static_power >= 0.0 && display.pixel_power >= 0.0) {
+                return Err(ConfigError::InvalidDisplay("negative power".to_string()));
+            }
+            let levels: Vec<f64> = match display.brightness {
+                Brightness::Constant(level) => vec![level],
+                Brightness::Profile(ref steps) => steps.iter().map(|s| s.level).collect(),
+            };
+            if levels.is_empty() {
+                return Err(ConfigError::InvalidDisplay("no brightness steps".to_string()));
+            }
+            if let Some(level) = levels.iter().find(|l| !(0.0..=1.0).contains(*l)) {
+                return Err(ConfigError::InvalidDisplay(format!("brightness {} is not within [0, 1]", level)));
+            }
+            if display.steps().windows(2).any(|w| w[0].from == w[1].from) {
+                return Err(ConfigError::InvalidDisplay("two brightness steps at the same time".to_string()));
+            }
+        }
+        if let Some(ref network) = self.network {
+            network.validate()?;
+        }
+        if let Some(ref abr) = self.abr {
+            // the policies choose on the buffer and throughput of the network
+            if self.network.is_none() {
+                return Err(ConfigError::InvalidAbr("a policy needs a network".to_string()));
+            }
+            if abr.qualities.is_empty() {
+                return Err(ConfigError::InvalidAbr("no qualities".to_string()));
+            }
+            if let Some(size) = abr.qualities.iter().find(|s| s.width == 0 || s.height == 0) {
+                return Err(ConfigError::InvalidAbr(format!("empty quality {}", size)));
+            }
+        }
+        if let Some(ref prefetch) = self.prefetch {
+            // what is prefetched for nothing is known in bytes
+            if self.network.is_none() && self.ladder.is_none() {
+                return Err(ConfigError::InvalidPrefetch("prefetching needs a ladder or a network".to_string()));
+            }
+            // without a hierarchy there is no level two to send
+            if prefetch.level == PrefetchLevel::LevelTwo && self.level_two == self.level_one {
+                return Err(ConfigError::InvalidPrefetch("prefetching level two needs a level two larger than level one".to_string()));
+            }
+            prefetch.validate()?;
+        }
+        Ok(())
+    }
+}
+
+// Defaults follow the setup used in auto_pc.sh: threshold 0.96, 20-frame segments and a 1440x1440
+// level one. Level two falls back to the level one size, which simulates without the hierarchy.
+#[derive(Debug, Clone)]
+pub struct SimulatorConfigBuilder {
+    threshold: f64,
+    segment: usize,
+    level_one: Size,
+    level_two: Option<Size>,
+    power: PowerProfile,
+    soc_scaling: SocScaling,
+    reproject_level_two: bool,
+    display: Option<DisplayModel>,
+    network: Option<NetworkModel>,
+    ladder: Option<BitrateLadder>,
+    abr: Option<Abr>,
+    prefetch: Option<Prefetch>,
+    opt: OptimizeVersion,
+}
+
+impl Default for SimulatorConfigBuilder {
+    fn default() -> Self {
+        SimulatorConfigBuilder {
+            threshold: 0.96,
+            segment: 20,
+            level_one: Size::new(1440, 1440),
+            level_two: None,
+            power: PowerProfile::default(),
+            soc_scaling: SocScaling::default(),
+            reproject_level_two: true,
+            display: None,
+            network: None,
+            ladder: None,
+            abr: None,
+            prefetch: None,
+            opt: OptimizeVersion::O0,
+        }
+    }
+}
+
+impl SimulatorConfigBuilder {
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn segment(mut self, segment: usize) -> Self {
+        self.segment = segment;
+        self
+    }
+
+    pub fn level_one(mut self, size: Size) -> Self {
+        self.level_one = size;
+        self
+    }
+
+    pub fn level_two(mut self, size: Size) -> Self {
+        self.level_two = Some(size);
+        self
+    }
+
+    pub fn power_profile(mut self, power: PowerProfile) -> Self {
+        self.power = power;
+        self
+    }
+
+    pub fn soc_scaling(mut self, soc_scaling: SocScaling) -> Self {
+        self.soc_scaling = soc_scaling;
+        self
+    }
+
+    // whether frames served from level two pay for reprojection, in proportion of its area
+    pub fn reproject_level_two(mut self, reproject_level_two: bool) -> Self {
+        self.reproject_level_two = reproject_level_two;
+        self
+    }
+
+    // panel whose power is added to every frame, none by default
+    pub fn display(mut self, display: DisplayModel) -> Self {
+        self.display = Some(display);
+        self
+    }
+
+    // transfers every segment over a network, whose radio power replaces the wifi constants
+    pub fn network(mut self, network: NetworkModel) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    // sizes the frames of every level in bytes, instead of the bitrates of the network
+    pub fn ladder(mut self, ladder: BitrateLadder) -> Self {
+        self.ladder = Some(ladder);
+        self
+    }
+
+    // chooses the quality of level three per segment, always the full frame by default
+    pub fn abr(mut self, abr: Abr) -> Self {
+        self.abr = Some(abr);
+        self
+    }
+
+    // fetches a level of unsure segments ahead instead of on a miss, nothing by default
+    pub fn prefetch(mut self, prefetch: Prefetch) -> Self {
+        self.prefetch = Some(prefetch);
+        self
+    }
+
+    pub fn opt(mut self, opt: OptimizeVersion) -> Self {
+        self.opt = opt;
+        self
+    }
+
+    pub fn build(self) -> Result<SimulatorConfig, ConfigError> {
+        let mut soc_scaling = self.soc_scaling;
+        soc_scaling.sort();
+        let mut display = self.display;
+        if let Some(ref mut display) = display {
+            display.sort();
+        }
+        let ladder = match self.ladder {
+            Some(ladder) => Some(ladder.checked()?),
+            None => None,
+        };
+        let config = SimulatorConfig {
+            threshold: self.threshold,
+            segment: self.segment,
+            level_one: self.level_one,
+            level_two: self.level_two.unwrap_or(self.level_one),
+            power: self.power,
+            soc_scaling,
+            reproject_level_two: self.reproject_level_two,
+            display,
+            network: self.network,
+            ladder,
+            abr: self.abr,
+            prefetch: self.prefetch,
+            opt: self.opt,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+// Values to sweep for each parameter. An empty list keeps the builder default, and an empty
+// `level_twos` means level two follows the level one size of the point.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ParameterGrid {
+    #[serde(rename = "threshold", default)]
+    pub thresholds: Vec<f64>,
+    #[serde(rename = "segment", default)]
+    pub segments: Vec<usize>,
+    #[serde(rename = "l1", default)]
+    pub level_ones: Vec<Size>,
+    #[serde(rename = "l2", default)]
+    pub level_twos: Vec<Size>,
+    #[serde(rename = "opt", default)]
+    pub opts: Vec<OptimizeVersion>,
+}
+
+fn or_default<T: Copy>(values: &[T]) -> Vec<Option<T>> {
+    if values.is_empty() {
+        vec![None]
+    } else {
+        values.iter().map(|&v| Some(v)).collect()
+    }
+}
+
+impl ParameterGrid {
+    // number of configs `configs` expands to
+    pub fn point_count(&self) -> usize {
+        [self.thresholds.len(), self.segments.len(), self.level_ones.len(), self.level_twos.len(), self.opts.len()]
+            .iter()
+            .map(|&n| n.max(1))
+            .product()
+    }
+
+    // cartesian product on top of `base`, ordered like the loops in auto_pc.sh: level two
+    // outermost, threshold innermost
+    pub fn configs(&self, base: &SimulatorConfigBuilder) -> Result<Vec<SimulatorConfig>, ConfigError> {
+        let mut configs = vec![];
+        for level_two in or_default(&self.level_twos) {
+            for level_one in or_default(&self.level_ones) {
+                for segment in or_default(&self.segments) {
+                    for opt in or_default(&self.opts) {
+                        for threshold in or_default(&self.thresholds) {
+                            let mut builder = base.clone();
+                            if let Some(size) = level_one {
+                                builder = builder.level_one(size);
+                            }
+                            if let Some(size) = level_two {
+                                builder = builder.level_two(size);
+                            }
+                            if let Some(segment) = segment {
+                                builder = builder.segment(segment);
+                            }
+                            if let Some(opt) = opt {
+                                builder = builder.opt(opt);
+                            }
+                            if let Some(threshold) = threshold {
+                                builder = builder.threshold(threshold);
+                            }
+                            configs.push(builder.build()?);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(configs)
+    }
+}
@@ -3,3 +3,10 @@ pub const FULL_SIZE_WIDTH_I32: i32 = 3840;
 pub const FULL_SIZE_HEIGHT_I32: i32 = 2160;
 pub const FULL_SIZE_WIDTH_USIZE: usize = 3840;
 pub const FULL_SIZE_HEIGHT_USIZE: usize = 2160;
+// tile size the SoC power constants were measured with
+pub const SOC_REFERENCE_WIDTH: usize = 1280;
+pub const SOC_REFERENCE_HEIGHT: usize = 720;
+// frame rate of a video when none is given
+pub const DEFAULT_FRAME_RATE: f64 = 30.0;
+// the power constants are in milliwatts
+pub const WATTS_PER_POWER_UNIT: f64 = 0.001;
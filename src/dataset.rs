@@ -0,0 +1,246 @@
+use config::ConfigError;
+use constants;
+use ds::{Frame, Viewport};
+use error::SimulatorError;
+
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::fs::{self, DirEntry, File};
+use std::path::Path;
+use std::str::FromStr;
+
+extern crate serde;
+extern crate serde_json;
+
+#[derive(Deserialize, Debug)]
+struct VideoObject {
+    frame_start: usize,
+    frame_end: usize,
+    #[allow(dead_code)]
+    size: usize,
+    cluster: Vec<usize>,
+}
+
+fn read_json_cluster_from_file(path: &str) -> Result<Vec<VideoObject>, SimulatorError> {
+    let file = File::open(path).map_err(|e| SimulatorError::io(path, e))?;
+
+    // Read the JSON contents of the file as an instance of `Vec[VideoObject]`.
+    let u = serde_json::from_reader(file).map_err(|e| SimulatorError::json(path, e))?;
+
+    // Return the `VideoObject`.
+    Ok(u)
+}
+
+// parse the `index`-th field of a trace line, naming the file and line number on failure
+pub(crate) fn parse_field<T: FromStr>(fields: &[&str], index: usize, name: &str, file: &str, line_no: usize) -> Result<T, SimulatorError> {
+    let field = fields.get(index)
+        .ok_or_else(|| SimulatorError::parse(file, line_no, &format!("missing {}", name)))?;
+    field.parse::<T>()
+        .map_err(|_| SimulatorError::parse(file, line_no, &format!("invalid {} `{}`", name, field)))
+}
+
+// `x,y,width,height` as written in both the tracedump and the user viewport files
+fn parse_viewport(conf: i32, coord: &str, file: &str, line_no: usize) -> Result<Viewport, SimulatorError> {
+    let coord: Vec<&str> = coord.split(',').collect();
+    let x = parse_field::<i32>(&coord, 0, "x", file, line_no)?;
+    let y = parse_field::<i32>(&coord, 1, "y", file, line_no)?;
+    let width = parse_field::<usize>(&coord, 2, "width", file, line_no)?;
+    let height = parse_field::<usize>(&coord, 3, "height", file, line_no)?;
+    Ok(Viewport::new(conf, x, y, width, height))
+}
+
+pub(crate) fn read_lines(path: &str) -> Result<Vec<String>, SimulatorError> {
+    let file = File::open(path).map_err(|e| SimulatorError::io(path, e))?;
+    BufReader::new(file).lines()
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| SimulatorError::io(path, e))
+}
+
+fn path_to_string<P: AsRef<Path>>(path: P) -> String {
+    path.as_ref().to_string_lossy().into_owned()
+}
+
+// Object paths of one video, built from its tracedump and cluster json. The viewports keep the
+// size of the detected objects; each simulator resizes them to its own level one size, so one
+// `VideoTraces` can be shared by runs with different parameters.
+#[derive(Debug)]
+pub struct VideoTraces {
+    path_list: Vec<Vec<Viewport>>,
+    name: String,
+    frame_rate: f64,
+}
+
+impl VideoTraces {
+    pub fn load<P: AsRef<Path>, Q: AsRef<Path>>(name: &str, dump_file: P, cluster_json: Q) -> Result<Self, SimulatorError> {
+        let dump_file = path_to_string(dump_file);
+        let cluster_json = path_to_string(cluster_json);
+        let mut traces: Vec<Viewport> = vec![];
+        let mut frame_id = 0;
+        let mut frame_list: Vec<Frame> = vec![];
+
+        for (line_no, line) in read_lines(&dump_file)?.iter().enumerate() {
+            let line_no = line_no + 1;
+            let id_vec: Vec<&str> = line.split(' ').collect();
+            frame_id = parse_field::<i32>(&id_vec, 0, "frame id", &dump_file, line_no)?;
+            let object_id = parse_field::<i32>(&id_vec, 1, "object id", &dump_file, line_no)?;
+            let coord = id_vec.get(2)
+                .ok_or_else(|| SimulatorError::parse(&dump_file, line_no, "missing viewport"))?;
+            let viewport = parse_viewport(100, coord, &dump_file, line_no)?;
+
+            if object_id == 0 {
+                if frame_id != 1 {
+                    frame_list.push(Frame::new(frame_id, &traces));
+                }
+                traces.clear();
+            }
+            traces.push(viewport);
+        }
+        // viewport in frame_list is not normalized using our fov size yet
+        frame_list.push(Frame::new(frame_id, &traces));
+
+        // integrate cluster_json and trace dump
+        let mut path_list: Vec<Vec<Viewport>> = vec![];
+        let video_objects = read_json_cluster_from_file(&cluster_json)?;
+        for video_object in video_objects {
+            if video_object.frame_start == 0 || video_object.frame_start > video_object.frame_end
+                || video_object.frame_end - 1 > frame_list.len() {
+                return Err(SimulatorError::ClusterOutOfRange {
+                    file: cluster_json,
+                    frame_start: video_object.frame_start,
+                    frame_end: video_object.frame_end,
+                    frame_count: frame_list.len(),
+                });
+            }
+            // frame_start and frame_end - 1 is for normalize the start id in trace dump file to 0
+            // so that we have the same start id as we get from user_view_port_result
+            let start = video_object.frame_start - 1;
+            let end = video_object.frame_end - 1;
+            let mut path: Vec<Viewport> = vec![];
+
+            // iterate all the frames from dumping data
+            for frame in frame_list[start..end].iter() {
+                for cluster in &video_object.cluster {
+                    if *cluster < frame.traces.len() {
+                        path.push(frame.traces[*cluster]);
+                    }
+                }
+                path_list.push(path.clone());
+                path.clear();
+            }
+//            println!("{}: {:?}", start, path_list[start]);
+        }
+
+        Ok(VideoTraces {
+            path_list,
+            name: name.to_string(),
+            frame_rate: constants::DEFAULT_FRAME_RATE,
+        })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.path_list.len()
+    }
+
+    // name of the video in the power profile, e.g. `Elephant` for `Elephant_WIFI`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // frames per second, 30 unless set
+    pub fn set_frame_rate(&mut self, frame_rate: f64) -> Result<(), ConfigError> {
+        if !(frame_rate > 0.0 && frame_rate.is_finite()) {
+            return Err(ConfigError::InvalidFrameRate(frame_rate));
+        }
+        self.frame_rate = frame_rate;
+        Ok(())
+    }
+
+    pub fn frame_rate(&self) -> f64 {
+        self.frame_rate
+    }
+
+    // object viewports of frame `index`, not yet resized
+    pub fn paths(&self, index: usize) -> &[Viewport] {
+        &self.path_list[index]
+    }
+}
+
+// Viewport of one user at every frame, read from a file in user_viewport_result.
+#[derive(Debug)]
+pub struct UserTrace {
+    user_file: String,
+    fov_list: Vec<Viewport>,
+}
+
+impl UserTrace {
+    pub fn load(user_file: &str) -> Result<Self, SimulatorError> {
+        let mut fov_list: Vec<Viewport> = vec![];
+
+        for (line_no, line) in read_lines(user_file)?.iter().enumerate() {
+            let line_no = line_no + 1;
+            let line_split: Vec<&str> = line.split(' ').collect();
+//            let key = (&line_split[0]).parse::<usize>().unwrap();
+            let conf = parse_field::<i32>(&line_split, 1, "confidence", user_file, line_no)?;
+            let extract = line_split.get(2)
+                .ok_or_else(|| SimulatorError::parse(user_file, line_no, "missing viewport"))?;
+            let u_fov = parse_viewport(conf, extract, user_file, line_no)?;
+//            // assume user_viewport file has key start from 0 and add one consecutively
+            fov_list.push(u_fov);
+        }
+        if fov_list.is_empty() {
+            return Err(SimulatorError::EmptyTrace { file: user_file.to_string() });
+        }
+
+        Ok(UserTrace {
+            user_file: user_file.to_string(),
+            fov_list,
+        })
+    }
+
+    pub fn user_file(&self) -> &str {
+        &self.user_file
+    }
+
+    pub fn fov_list(&self) -> &[Viewport] {
+        &self.fov_list
+    }
+}
+
+// A video together with the traces of every user who watched it. Loaded once and borrowed by
+// as many simulators as needed. User files that fail to load end up in `skipped` instead of
+// failing the whole dataset, unless none is left.
+#[derive(Debug)]
+pub struct Dataset {
+    pub video: VideoTraces,
+    pub users: Vec<UserTrace>,
+    pub skipped: Vec<SimulatorError>,
+}
+
+impl Dataset {
+    pub fn load<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(name: &str, user_dir: P, dump_file: Q, cluster_json: R) -> Result<Self, SimulatorError> {
+        let dir_name = path_to_string(&user_dir);
+        let mut user_paths: Vec<DirEntry> = fs::read_dir(&user_dir)
+            .and_then(|entries| entries.collect())
+            .map_err(|e| SimulatorError::io(&dir_name, e))?;
+        user_paths.sort_by_key(|dir| dir.path());
+
+        let video = VideoTraces::load(name, dump_file, cluster_json)?;
+        let mut users = vec![];
+        let mut skipped = vec![];
+        for path in user_paths {
+            match UserTrace::load(&path_to_string(path.path())) {
+                Ok(user) => users.push(user),
+                Err(e) => skipped.push(e),
+            }
+        }
+        if users.is_empty() {
+            return Err(SimulatorError::NoUsers { dir: dir_name, skipped });
+        }
+
+        Ok(Dataset {
+            video,
+            users,
+            skipped,
+        })
+    }
+}
@@ -1,4 +1,61 @@
 use constants;
+use error::SimulatorError;
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use std::fmt;
+use std::str::FromStr;
+
+// width x height of a viewport tile, kept as named fields so the two can't be swapped silently
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Size {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Size {
+    pub fn new(width: usize, height: usize) -> Self {
+        Size {
+            width,
+            height,
+        }
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+// parse `WIDTHxHEIGHT`, e.g. `1440x1440`
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let dims: Vec<&str> = s.split('x').collect();
+        if dims.len() != 2 {
+            return Err(format!("expected WIDTHxHEIGHT, got `{}`", s));
+        }
+        let width = dims[0].parse::<usize>().map_err(|_| format!("invalid width `{}`", dims[0]))?;
+        let height = dims[1].parse::<usize>().map_err(|_| format!("invalid height `{}`", dims[1]))?;
+        Ok(Size::new(width, height))
+    }
+}
+
+impl Serialize for Size {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Size {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct Viewport {
@@ -24,7 +81,7 @@ impl Viewport {
         let mut x = other_viewport.x + ((other_viewport.width as i32 - width as i32) / 2);
         let y = other_viewport.y + ((other_viewport.height as i32 - height as i32) / 2);
         if x < 0 {
-            x = constants::FULL_SIZE_WIDTH_I32 + x;
+            x += constants::FULL_SIZE_WIDTH_I32;
         }
         Viewport {
             conf: other_viewport.conf,
@@ -35,7 +92,12 @@ impl Viewport {
         }
     }
 
-    pub fn get_cover_result(&self, user_fov: &Viewport) -> f64 {
+    // confidence of the viewport in percent, of the eye tracker or the object detection
+    pub fn get_conf(&self) -> i32 {
+        self.conf
+    }
+
+    pub fn get_cover_result(&self, user_fov: &Viewport) -> Result<f64, SimulatorError> {
         let mut total_x = 0;
         let self_rightmost = self.x + self.width as i32;
         let user_rightmost = user_fov.x + user_fov.width as i32;
@@ -50,7 +112,7 @@ impl Viewport {
                         let right_2 = constants::FULL_SIZE_WIDTH_I32;
                         total_x += right_2 - left_2;
                     },
-                    m if m <= constants::FULL_SIZE_WIDTH_I32 && m >= 0 => {
+                    m if (0..=constants::FULL_SIZE_WIDTH_I32).contains(&m) => {
                         if self.x + self.width as i32 - constants::FULL_SIZE_WIDTH_I32 > user_fov.x {
                             let left = user_fov.x;
                             let right = i32::min(self_rightmost - constants::FULL_SIZE_WIDTH_I32, user_rightmost);
@@ -62,10 +124,10 @@ impl Viewport {
                             total_x += right - left;
                         }
                     },
-                    _ => assert!(false),
+                    _ => return Err(self.out_of_panorama(user_fov)),
                 }
             },
-            n if n <= constants::FULL_SIZE_WIDTH_I32 && n >= 0 => {
+            n if (0..=constants::FULL_SIZE_WIDTH_I32).contains(&n) => {
                 match user_rightmost {
                     m if m > constants::FULL_SIZE_WIDTH_I32 => {
                         if self.x < (user_rightmost - constants::FULL_SIZE_WIDTH_I32) {
@@ -79,17 +141,17 @@ impl Viewport {
                             total_x += right_2 - left_2;
                         }
                     },
-                    m if m <= constants::FULL_SIZE_WIDTH_I32 && m >= 0 => {
+                    m if (0..=constants::FULL_SIZE_WIDTH_I32).contains(&m) => {
                         let left = i32::max(self.x, user_fov.x);
                         let right = i32::min(self_rightmost, user_rightmost);
                         if right - left > 0 {
                             total_x = right - left;
                         }
                     },
-                    _ => assert!(false),
+                    _ => return Err(self.out_of_panorama(user_fov)),
                 }
             },
-            _ => assert!(false),
+            _ => return Err(self.out_of_panorama(user_fov)),
         }
 
         let bottom = i32::max(self.y, user_fov.y);
@@ -102,12 +164,15 @@ impl Viewport {
         let total_x = i32::abs(total_x);
         let ratio: f64 = (total_x * total_y) as f64 / (user_fov.width * user_fov.height) as f64;
         if ratio > 1.0 {
-            println!("total_x {}", total_x);
-            println!("self {:?}", self);
-            println!("user {:?}", user_fov);
-            assert!(false);
+            return Err(SimulatorError::GeometryInvariant(
+                format!("cover ratio {} > 1 (total_x {}, self {:?}, user {:?})", ratio, total_x, self, user_fov)));
         }
-        return ratio;
+        Ok(ratio)
+    }
+
+    fn out_of_panorama(&self, user_fov: &Viewport) -> SimulatorError {
+        SimulatorError::GeometryInvariant(
+            format!("viewport outside of the panorama (self {:?}, user {:?})", self, user_fov))
     }
 }
 
@@ -119,8 +184,8 @@ pub struct Frame {
 }
 
 impl Frame {
-    pub fn new(index: i32, traces: &Vec<Viewport>) -> Self {
-        let t = traces.clone();
+    pub fn new(index: i32, traces: &[Viewport]) -> Self {
+        let t = traces.to_vec();
         Frame {
             index,
             traces: t,
@@ -133,4 +198,5 @@ impl Frame {
 fn test_trace() {
     let t_1 = Viewport::new(100, 700, 700, 1200, 1200);
     let t_2 = Viewport::create_new_with_size(&t_1, 2000, 2000);
+    assert_eq!(t_2.get_cover_result(&t_1).unwrap(), 1.0);
 }
@@ -0,0 +1,90 @@
+use config::ConfigError;
+use ds::Size;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+extern crate serde_json;
+
+#[derive(Debug)]
+pub enum SimulatorError {
+    Io { path: String, error: io::Error },
+    Parse { file: String, line: usize, message: String },
+    ClusterOutOfRange { file: String, frame_start: usize, frame_end: usize, frame_count: usize },
+    MissingPowerConstant { name: String },
+    UnknownPowerConstant { file: String, name: String },
+    UnknownVideo { video: String },
+    UnknownBitrate { video: String, size: Size },
+    MissingSamples { rail: String, start: f64, end: f64 },
+    UnknownDevice { file: String, device: String },
+    GeometryInvariant(String),
+    EmptyTrace { file: String },
+    NoUsers { dir: String, skipped: Vec<SimulatorError> },
+    Config(ConfigError),
+}
+
+impl SimulatorError {
+    pub(crate) fn io(path: &str, error: io::Error) -> Self {
+        SimulatorError::Io { path: path.to_string(), error }
+    }
+
+    pub(crate) fn parse(file: &str, line: usize, message: &str) -> Self {
+        SimulatorError::Parse { file: file.to_string(), line, message: message.to_string() }
+    }
+
+    // serde_json reports io failures, syntax errors and schema mismatches through the same type
+    pub(crate) fn json(file: &str, error: serde_json::Error) -> Self {
+        if error.is_io() {
+            SimulatorError::Io { path: file.to_string(), error: error.into() }
+        } else {
+            SimulatorError::Parse { file: file.to_string(), line: error.line(), message: error.to_string() }
+        }
+    }
+}
+
+impl fmt::Display for SimulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SimulatorError::Io { ref path, ref error } => write!(f, "{}: {}", path, error),
+            SimulatorError::Parse { ref file, line, ref message } => write!(f, "{}:{}: {}", file, line, message),
+            SimulatorError::ClusterOutOfRange { ref file, frame_start, frame_end, frame_count } =>
+                write!(f, "{}: cluster frames {}..{} exceed the {} frames of the tracedump",
+                       file, frame_start, frame_end, frame_count),
+            SimulatorError::MissingPowerConstant { ref name } => write!(f, "missing power constant `{}`", name),
+            SimulatorError::UnknownPowerConstant { ref file, ref name } =>
+                write!(f, "{}: power constant `{}` is not named <Video>_WIFI, _SOC or _DECODE", file, name),
+            SimulatorError::UnknownVideo { ref video } => write!(f, "no power constants for video `{}`", video),
+            SimulatorError::UnknownBitrate { ref video, size } => write!(f, "no bitrate for video `{}` at {}", video, size),
+            SimulatorError::MissingSamples { ref rail, start, end } =>
+                write!(f, "the logs of rail `{}` do not cover {}s to {}s", rail, start, end),
+            SimulatorError::UnknownDevice { ref file, ref device } => write!(f, "{}: no power profile for device `{}`", file, device),
+            SimulatorError::GeometryInvariant(ref message) => write!(f, "geometry invariant violated: {}", message),
+            SimulatorError::EmptyTrace { ref file } => write!(f, "{}: no frame to simulate", file),
+            SimulatorError::NoUsers { ref dir, ref skipped } => {
+                write!(f, "{}: no user trace to simulate", dir)?;
+                for error in skipped {
+                    write!(f, "\n  skipped {}", error)?;
+                }
+                Ok(())
+            }
+            SimulatorError::Config(ref error) => write!(f, "invalid configuration: {}", error),
+        }
+    }
+}
+
+impl Error for SimulatorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            SimulatorError::Io { ref error, .. } => Some(error),
+            SimulatorError::Config(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for SimulatorError {
+    fn from(error: ConfigError) -> Self {
+        SimulatorError::Config(error)
+    }
+}
@@ -0,0 +1,132 @@
+use dataset::{parse_field, read_lines};
+use error::SimulatorError;
+use power::PowerConstants;
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+// Samples of every rail of one or more measurement logs, by rail, in time order. Logs are csv
+// lines of `timestamp,rail,mW` with the timestamp in seconds; a header line is skipped.
+#[derive(Debug, Clone, Default)]
+pub struct PowerLog {
+    rails: HashMap<String, Vec<(f64, f64)>>,
+}
+
+// an empty line, or the first line when its field `numeric` is not a number, as in a header
+fn skip_line(line_no: usize, fields: &[&str], numeric: usize) -> bool {
+    fields.iter().all(|f| f.is_empty()) || (line_no == 1 && fields.get(numeric).is_none_or(|f| f.parse::<f64>().is_err()))
+}
+
+impl PowerLog {
+    pub fn load(paths: &[String]) -> Result<Self, SimulatorError> {
+        let mut log = PowerLog::default();
+        for path in paths {
+            for (line_no, line) in read_lines(path)?.iter().enumerate() {
+                let line_no = line_no + 1;
+                let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+                if skip_line(line_no, &fields, 0) {
+                    continue;
+                }
+                let timestamp = parse_field::<f64>(&fields, 0, "timestamp", path, line_no)?;
+                let rail = fields.get(1).ok_or_else(|| SimulatorError::parse(path, line_no, "missing rail"))?;
+                let power = parse_field::<f64>(&fields, 2, "power", path, line_no)?;
+                log.rails.entry(rail.to_string()).or_default().push((timestamp, power));
+            }
+        }
+        for samples in log.rails.values_mut() {
+            samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+        Ok(log)
+    }
+
+    pub fn samples(&self, rail: &str) -> Option<&[(f64, f64)]> {
+        self.rails.get(rail).map(|s| s.as_slice())
+    }
+
+    // Energy of `rail` between `start` and `end` in mW times seconds, integrating the samples with
+    // the trapezoidal rule. The power at the bounds is interpolated from the closest samples.
+    pub fn energy(&self, rail: &str, start: f64, end: f64) -> Option<f64> {
+        let samples = self.samples(rail)?;
+        if samples.len() < 2 || start < samples[0].0 || end > samples[samples.len() - 1].0 {
+            return None;
+        }
+        let power_at = |time: f64| {
+            let i = samples.iter().position(|s| s.0 >= time).unwrap();
+            match i {
+                0 => samples[0].1,
+                i => {
+                    let (low, high) = (samples[i - 1], samples[i]);
+                    low.1 + (high.1 - low.1) * (time - low.0) / (high.0 - low.0)
+                }
+            }
+        };
+        let mut points = vec![(start, power_at(start))];
+        points.extend(samples.iter().filter(|s| s.0 > start && s.0 < end).cloned());
+        points.push((end, power_at(end)));
+        Some(points.windows(2).map(|w| (w[1].0 - w[0].0) * (w[0].1 + w[1].1) / 2.0).sum())
+    }
+}
+
+// A window of a log measuring one rail for a power constant, a line `name,rail,start,end` of a
+// label file. A constant measured on several rails is the sum of their power; a rail measured in
+// several windows is averaged over all of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub name: String,
+    pub rail: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+pub fn read_labels(path: &str) -> Result<Vec<Label>, SimulatorError> {
+    let mut labels = vec![];
+    for (line_no, line) in read_lines(path)?.iter().enumerate() {
+        let line_no = line_no + 1;
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        if skip_line(line_no, &fields, 2) {
+            continue;
+        }
+        let label = Label {
+            name: fields[0].to_string(),
+            rail: fields.get(1).ok_or_else(|| SimulatorError::parse(path, line_no, "missing rail"))?.to_string(),
+            start: parse_field::<f64>(&fields, 2, "start", path, line_no)?,
+            end: parse_field::<f64>(&fields, 3, "end", path, line_no)?,
+        };
+        if label.end.partial_cmp(&label.start) != Some(Ordering::Greater) {
+            return Err(SimulatorError::parse(path, line_no, &format!("window {} to {} is empty", label.start, label.end)));
+        }
+        labels.push(label);
+    }
+    Ok(labels)
+}
+
+// Average power of every labelled constant, in the order the labels first name them.
+pub fn fit(log: &PowerLog, labels: &[Label]) -> Result<Vec<PowerConstants>, SimulatorError> {
+    // energy and duration of every rail of every constant
+    let mut names: Vec<&str> = vec![];
+    let mut rails: Vec<(&str, &str, f64, f64)> = vec![];
+    for label in labels {
+        let energy = log.energy(&label.rail, label.start, label.end).ok_or_else(|| SimulatorError::MissingSamples {
+            rail: label.rail.clone(),
+            start: label.start,
+            end: label.end,
+        })?;
+        if !names.contains(&label.name.as_str()) {
+            names.push(&label.name);
+        }
+        match rails.iter_mut().find(|r| r.0 == label.name && r.1 == label.rail) {
+            Some(rail) => {
+                rail.2 += energy;
+                rail.3 += label.end - label.start;
+            }
+            None => rails.push((&label.name, &label.rail, energy, label.end - label.start)),
+        }
+    }
+    Ok(names.iter().map(|&name| {
+        let value = rails.iter()
+            .filter(|r| r.0 == name)
+            .map(|r| r.2 / r.3)
+            .sum();
+        PowerConstants::new(name, value)
+    }).collect())
+}
@@ -0,0 +1,101 @@
+use config::ConfigError;
+use ds::Size;
+use error::SimulatorError;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+extern crate serde_json;
+
+// One encode of a video at `size`, streamed at `kbps`.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct Rung {
+    pub size: Size,
+    pub kbps: f64,
+}
+
+// Bytes of every segment of a real encode at `size`, for segments as long as the simulated ones.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SegmentSizes {
+    pub size: Size,
+    pub bytes: Vec<f64>,
+}
+
+// Encodes of one video. A size without segment sizes is streamed at the bitrate of the rung of the
+// same size, or else interpolated by area between the rungs around it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct VideoLadder {
+    #[serde(default)]
+    pub rungs: Vec<Rung>,
+    #[serde(default)]
+    pub segments: Vec<SegmentSizes>,
+}
+
+// Bitrate ladders by video. Written in json like
+// `{"videos": {"Paris": {"rungs": [{"size": "1440x1440", "kbps": 9000}, {"size": "3840x2160", "kbps": 40000}],
+//   "segments": [{"size": "3840x2160", "bytes": [5000000, 4800000]}]}}}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BitrateLadder {
+    pub videos: HashMap<String, VideoLadder>,
+}
+
+fn area(size: Size) -> f64 {
+    size.width as f64 * size.height as f64
+}
+
+impl VideoLadder {
+    // Bytes of one frame at `size` in the segment at `segment`, `frames` frames long. None when
+    // the ladder has neither segment sizes at this size nor rungs.
+    pub fn frame_bytes(&self, size: Size, segment: usize, frames: usize, frame_rate: f64) -> Option<f64> {
+        let encoded = self.segments.iter()
+            .find(|s| s.size == size)
+            .and_then(|s| s.bytes.get(segment));
+        if let Some(bytes) = encoded {
+            return Some(bytes / frames as f64);
+        }
+        self.kbps(size).map(|kbps| kbps * 1000.0 / 8.0 / frame_rate)
+    }
+
+    pub fn kbps(&self, size: Size) -> Option<f64> {
+        if let Some(rung) = self.rungs.iter().find(|r| r.size == size) {
+            return Some(rung.kbps);
+        }
+        let a = area(size);
+        let by_area = |x: &&Rung, y: &&Rung| area(x.size).total_cmp(&area(y.size));
+        let low = self.rungs.iter().filter(|r| area(r.size) <= a).max_by(by_area);
+        let high = self.rungs.iter().filter(|r| area(r.size) > a).min_by(by_area);
+        match (low, high) {
+            (Some(low), Some(high)) =>
+                Some(low.kbps + (high.kbps - low.kbps) * (a - area(low.size)) / (area(high.size) - area(low.size))),
+            // beyond the ladder the bitrate grows with the area
+            (Some(rung), None) | (None, Some(rung)) => Some(rung.kbps * a / area(rung.size)),
+            (None, None) => None,
+        }
+    }
+}
+
+impl BitrateLadder {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SimulatorError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+        let ladder: BitrateLadder = serde_json::from_reader(file).map_err(|e| SimulatorError::json(&name, e))?;
+        Ok(ladder.checked()?)
+    }
+
+    pub(crate) fn checked(self) -> Result<Self, ConfigError> {
+        for (video, ladder) in &self.videos {
+            if let Some(r) = ladder.rungs.iter().find(|r| !(r.kbps > 0.0 && r.kbps.is_finite()) || area(r.size) == 0.0) {
+                return Err(ConfigError::InvalidLadder(format!("{} at {} kbps for `{}`", r.size, r.kbps, video)));
+            }
+            if ladder.segments.iter().any(|s| s.bytes.iter().any(|b| !(*b >= 0.0 && b.is_finite()))) {
+                return Err(ConfigError::InvalidLadder(format!("negative segment size for `{}`", video)));
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn video(&self, video: &str) -> Option<&VideoLadder> {
+        self.videos.get(video)
+    }
+}
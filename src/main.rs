@@ -7,205 +7,392 @@ extern crate serde_json;
 mod simulator;
 mod ds;
 mod constants;
+mod config;
+mod dataset;
+mod error;
+mod power;
+mod cli;
+mod manifest;
+mod sweep;
+mod report;
+mod output;
+mod timeline;
+mod battery;
+mod fit;
+mod network;
+mod ladder;
+mod abr;
+mod prefetch;
 
-pub use simulator::{Simulator, PowerConstants};
-pub use ds::{Viewport, Frame};
+pub use simulator::Simulator;
+pub use power::{PowerConstants, PowerProfile, VideoPower, PowerSource, Energy, DisplayModel, Brightness, BrightnessStep, SocScaling, ScalePoint, SocComponents, read_power_consumption_from_file,
+                write_power_consumption};
+pub use ds::{Viewport, Frame, Size};
+pub use config::{SimulatorConfig, SimulatorConfigBuilder, ConfigError, OptimizeVersion, ParameterGrid};
+pub use dataset::{Dataset, VideoTraces, UserTrace};
+pub use error::SimulatorError;
+pub use cli::{Command, Mode, Options, FitOptions, CliError, parse, USAGE};
+pub use manifest::{Manifest, VideoEntry};
+pub use sweep::{Sweep, Job, parallel_map, default_workers};
+pub use report::{SimulationReport, SimulationParameters, SummaryReport, Stats};
+pub use output::{Format, Row, RowWriter};
+pub use timeline::{Timeline, FrameEnergy, SegmentEnergy, TimelineRow};
+pub use battery::{Battery, DischargePoint, Discharge, ChargePoint, BatteryRow};
+pub use fit::{PowerLog, Label, read_labels, fit};
+pub use network::{NetworkModel, NetworkReport, VideoBitrate, RadioPower, Transfer, BandwidthTrace, PlaybackBuffer,
+                  SegmentFetch, Playback};
+pub use ladder::{BitrateLadder, VideoLadder, Rung, SegmentSizes};
+pub use abr::{Abr, AbrModel, AbrPolicy, AbrPolicyConfig, AbrState, FixedQuality, BufferBased, ThroughputBased, Mpc};
+pub use prefetch::{Prefetch, PrefetchLevel, PrefetchReport};
 
 use std::env;
-use std::path::Path;
-use std::fs::{self};
 use std::fs::File;
-use std::error::Error;
-use std::fs::DirEntry;
+use std::io::{self, Write};
+use std::process;
 
-#[derive(Debug, Copy, Clone)]
-enum OptimizeVersion {
-    O0,
-    O1,
+type JobResult = (Job, Result<SimulationReport, SimulatorError>);
+
+// reports of one user group in user order, reporting failed users on stderr so a single bad
+// trace does not end the batch
+fn succeeded<'r>(dataset: &Dataset, results: &'r [JobResult]) -> Vec<Option<&'r SimulationReport>> {
+    results.iter().map(|&(job, ref result)| match *result {
+        Ok(ref report) => Some(report),
+        Err(ref e) => {
+            eprintln!("skipping {}: {}", dataset.users[job.user].user_file(), e);
+            None
+        }
+    }).collect()
 }
 
-fn read_power_consumption_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<PowerConstants>, Box<Error>> {
-    let file = File::open(path)?;
-    let u = serde_json::from_reader(file)?;
-    Ok(u)
+// historical line of `compare`: the hit ratios of every user with level two, level one only and
+// level two with O1
+fn write_compare(users: &[Option<&SimulationReport>], base: &[Option<&SimulationReport>], opt: &[Option<&SimulationReport>], output: &mut Output) -> io::Result<()> {
+    for ((user, base), opt) in users.iter().zip(base).zip(opt) {
+        if let (Some(user), Some(base), Some(opt)) = (user, base, opt) {
+            match output.rows.format() {
+                Format::Plain => writeln!(output.rows.get_mut(), "l1-l2-hier: {:?}, l1-only: {:?}, l1-l2-opt-hier: {:?}",
+                                          user.hit_ratios, base.hit_ratios, opt.hit_ratios)?,
+                _ => write_users(&[*user, *base, *opt], output)?,
+            }
+        }
+    }
+    Ok(())
 }
 
-#[allow(dead_code)]
-fn compare_each_simulation(object_result: &String, dump_file: &String, cluster_json: &String,
-                           threshold: f64, segment: usize, fov_width: usize, fov_height: usize,
-                           level_two_width: usize, level_two_height: usize,
-                           power_constant_4k_360: &Vec<PowerConstants>, power_constant_1080p: &Vec<PowerConstants>) {
-    let mut user_paths: Vec<DirEntry> = fs::read_dir(&object_result).unwrap().map(|r| r.unwrap()).collect();
-    user_paths.sort_by_key(|dir| dir.path());
-
-    for path in user_paths {
-        let user_file = path.path().to_str().unwrap().to_string();
-//        println!("{}", user_file);
-        let mut simulator = Simulator::new(&user_file, &dump_file, &cluster_json, threshold,
-                                           segment, fov_width, fov_height, level_two_width, level_two_height,
-                                           power_constant_4k_360.clone(),
-                                           power_constant_1080p.clone(), false);
-        simulator.simulate();
-        simulator.power_consumption();
-        let mut simulator_base = Simulator::new(&user_file, &dump_file, &cluster_json,
-                                                threshold, segment, fov_width, fov_height, fov_width,
-                                                fov_height, power_constant_4k_360.clone(),
-                                                power_constant_1080p.clone(), false);
-        simulator_base.simulate();
-        simulator_base.power_consumption();
-        let mut simulator_opt = Simulator::new(&user_file, &dump_file, &cluster_json,
-                                               threshold, segment, fov_width, fov_height,
-                                               level_two_width, level_two_height, power_constant_4k_360.clone(),
-                                               power_constant_1080p.clone(), true);
-        simulator_opt.simulate();
-        simulator_opt.power_consumption();
-        println!("l1-l2-hier: {:?}, l1-only: {:?}, l1-l2-opt-hier: {:?}", simulator.get_hit_ratios(),
-                 simulator_base.get_hit_ratios(), simulator_opt.get_hit_ratios());
-    }
-}
-
-fn single_simulate_pc(user_paths: &Vec<DirEntry>, dump_file: &String, cluster_json: &String,
-                      threshold: f64, segment: usize, fov_width: usize, fov_height: usize,
-                      level_two_width: usize, level_two_height: usize,
-                      power_constants_4k_360: &Vec<PowerConstants>, power_constant_1080p: &Vec<PowerConstants>, opt: OptimizeVersion) {
-    let mut pc_tuple: (f64, f64) = (0.0, 0.0);
-    let mut hit_ratios: (f64, f64, f64) = (0.0, 0.0, 0.0);
-    let mut count = 0;
-    let mut no_resend_segment_rate = 0.0;
-
-    for path in user_paths {
-        let user_file = path.path().to_str().unwrap().to_string();
-        let mut simulator = {
-            match opt {
-                OptimizeVersion::O0 =>
-                    Simulator::new(&user_file, &dump_file, &cluster_json, threshold, segment, fov_width, fov_height,
-                                   level_two_width, level_two_height, power_constants_4k_360.clone(),
-                                   power_constant_1080p.clone(), false),
-                OptimizeVersion::O1 =>
-                    Simulator::new(&user_file, &dump_file, &cluster_json, threshold, segment, fov_width, fov_height,
-                                   level_two_width, level_two_height, power_constants_4k_360.clone(),
-                                   power_constant_1080p.clone(), true)
+fn write_users(users: &[&SimulationReport], output: &mut Output) -> io::Result<()> {
+    for user in users {
+        output.rows.write(*user)?;
+    }
+    Ok(())
+}
+
+// one line of `simulate` and `sweep`, the historical space separated means for plain output
+fn write_summary(mode: Mode, summary: &SummaryReport, output: &mut Output) -> io::Result<()> {
+    let hit_ratios = &summary.hit_ratios;
+    match (output.rows.format(), mode) {
+        // wifi soc threshold l1 l2 l3 segment-hit
+        (Format::Plain, Mode::Power) => writeln!(output.rows.get_mut(), "{} {} {} {} {} {} {}", summary.wifi_pc.mean, summary.soc_pc.mean,
+                                                 summary.parameters.threshold, hit_ratios[0].mean, hit_ratios[1].mean, hit_ratios[2].mean,
+                                                 summary.segment_hit.mean),
+        // l1 l2 l3 threshold
+        (Format::Plain, Mode::Hit) => writeln!(output.rows.get_mut(), "{} {} {} {}", hit_ratios[0].mean, hit_ratios[1].mean,
+                                               hit_ratios[2].mean, summary.parameters.threshold),
+        _ => output.rows.write(summary),
+    }
+}
+
+// where the rows of one video go
+struct Output {
+    name: String,
+    rows: RowWriter<Box<dyn Write>>,
+}
+
+impl Output {
+    fn stdout(format: Format) -> Self {
+        Output { name: "<stdout>".to_string(), rows: RowWriter::new(format, Box::new(io::stdout())) }
+    }
+
+    fn create(path: &str, format: Format) -> Result<Self, SimulatorError> {
+        let file = File::create(path).map_err(|e| SimulatorError::io(path, e))?;
+        Ok(Output { name: path.to_string(), rows: RowWriter::new(format, Box::new(file)) })
+    }
+
+    fn finish(&mut self) -> Result<(), SimulatorError> {
+        self.rows.finish().map_err(|e| SimulatorError::io(&self.name, e))
+    }
+
+    fn write_with(&mut self, write: impl FnOnce(&mut Output) -> io::Result<()>) -> Result<(), SimulatorError> {
+        write(self).map_err(|e| SimulatorError::io(&self.name, e))
+    }
+}
+
+fn write_timelines(users: &[&SimulationReport], output: &mut Output) -> io::Result<()> {
+    for user in users {
+        if let Some(ref timeline) = user.timeline {
+            for row in TimelineRow::rows(user, timeline) {
+                output.rows.write(&row)?;
             }
-        };
-        simulator.simulate();
-        pc_tuple.0 += simulator.get_wifi_pc();
-        pc_tuple.1 += simulator.get_soc_pc();
-
-        let x = simulator.get_hit_ratios();
-        hit_ratios.0 += x[0];
-        hit_ratios.1 += x[1];
-        hit_ratios.2 += x[2];
-
-        no_resend_segment_rate += 1.0 - simulator.get_segment_resend_cnt() as f64 / simulator.get_segment_count() as f64;
-
-        count += 1;
-//        simulator_opt.print_power_consumption();
-    }
-
-    hit_ratios.0 /= count as f64;
-    hit_ratios.1 /= count as f64;
-    hit_ratios.2 /= count as f64;
-
-    no_resend_segment_rate /= count as f64;
-
-    // wifi soc screen level_2
-//    println!("{} {} {} {}", pc_tuple.0 / count as f64, pc_tuple.1 / count as f64, fov_width, level_two_width);
-    println!("{} {} {} {} {} {} {}", pc_tuple.0 / count as f64, pc_tuple.1 / count as f64, threshold, hit_ratios.0, hit_ratios.1, hit_ratios.2, no_resend_segment_rate);
-}
-
-fn single_simulate_hit(user_paths: &Vec<DirEntry>, dump_file: &String, cluster_json: &String,
-                       threshold: f64, segment: usize, fov_width: usize, fov_height: usize,
-                       level_two_width: usize, level_two_height: usize,
-                       power_constants_4k_360: &Vec<PowerConstants>, power_constant_1080p: &Vec<PowerConstants>, opt: OptimizeVersion) {
-    let mut hit_ratios: (f64, f64, f64) = (0.0, 0.0, 0.0);
-    let mut count = 0;
-    for path in user_paths {
-        let user_file = path.path().to_str().unwrap().to_string();
-        let mut simulator = {
-            match opt {
-                OptimizeVersion::O0 =>
-                    Simulator::new(&user_file, &dump_file, &cluster_json, threshold, segment, fov_width, fov_height,
-                                   level_two_width, level_two_height, power_constants_4k_360.clone(),
-                                   power_constant_1080p.clone(), false),
-                OptimizeVersion::O1 =>
-                    Simulator::new(&user_file, &dump_file, &cluster_json, threshold, segment, fov_width, fov_height,
-                                   level_two_width, level_two_height, power_constants_4k_360.clone(),
-                                   power_constant_1080p.clone(), true)
+        }
+    }
+    Ok(())
+}
+
+// the summaries of one video and, when asked for, the report and the timeline of every user and
+// the discharge of a battery for every summary
+struct VideoOutput {
+    summary: Output,
+    per_user: Option<Output>,
+    timeline: Option<Output>,
+    battery: Option<(Battery, Output)>,
+}
+
+fn create_optional(path: Option<&str>, format: Format) -> Result<Option<Output>, SimulatorError> {
+    match path {
+        Some(path) => Ok(Some(Output::create(path, format)?)),
+        None => Ok(None),
+    }
+}
+
+impl VideoOutput {
+    fn new(summary: Option<&str>, per_user: Option<&str>, timeline: Option<&str>, format: Format) -> Result<Self, SimulatorError> {
+        Ok(VideoOutput {
+            summary: match summary {
+                Some(path) => Output::create(path, format)?,
+                None => Output::stdout(format),
+            },
+            per_user: create_optional(per_user, format)?,
+            timeline: create_optional(timeline, format)?,
+            battery: None,
+        })
+    }
+
+    fn with_battery(mut self, battery: Option<&Battery>, path: Option<&str>) -> Result<Self, SimulatorError> {
+        match (battery, path) {
+            (Some(battery), Some(path)) => {
+                let output = Output::create(path, self.summary.rows.format())?;
+                self.battery = Some((battery.clone(), output));
+            }
+            (None, Some(path)) => {
+                return Err(ConfigError::InvalidBattery(format!("no battery model to write {}", path)).into());
             }
-        };
-        simulator.simulate();
-        let x = simulator.get_hit_ratios();
-        hit_ratios.0 += x[0];
-        hit_ratios.1 += x[1];
-        hit_ratios.2 += x[2];
-        count += 1;
-    }
-    hit_ratios.0 /= count as f64;
-    hit_ratios.1 /= count as f64;
-    hit_ratios.2 /= count as f64;
-
-//    println!("{} {} {} {} {}", hit_ratios.0, hit_ratios.1, hit_ratios.2, fov_width, level_two_width);
-    println!("{} {} {} {}", hit_ratios.0, hit_ratios.1, hit_ratios.2, threshold);
-}
-
-//#[allow(dead_code)]
-//fn batch_simulation(object_result: &String, dump_file: &String, cluster_json: &String,
-//                    threshold: f64, segment: usize, power_constants: &Vec<PowerConstants>,
-//                    power_constant_1224: &Vec<PowerConstants>) {
-//    let mut user_paths: Vec<DirEntry> = fs::read_dir(&object_result).unwrap().map(|r| r.unwrap()).collect();
-//    user_paths.sort_by_key(|dir| dir.path());
-//
-//    for screen in PythonFor(1200, 2001, 100) {
-//        for level_2 in PythonFor(2200, 3401, 100) {
-//            single_simulate_pc(&user_paths, &dump_file, &cluster_json, threshold, segment,
-//                               screen as usize, screen as usize,
-//                               level_2 as usize, level_2 as usize,
-//                               power_constants, power_constant_1224);
-//        }
-//    }
-//}
+            _ => {}
+        }
+        Ok(self)
+    }
 
-#[allow(dead_code)]
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let object_result = args[1].clone();
-    let dump_file: String = args[2].clone();
-    let cluster_json: String = args[3].clone();
-    let threshold = args[4].parse::<f64>().unwrap();
-    let segment = args[5].parse::<usize>().unwrap();
-    let width = args[6].parse::<usize>().unwrap();
-    let height = args[7].parse::<usize>().unwrap();
-    let l2_width = args[8].parse::<usize>().unwrap();
-    let l2_height = args[9].parse::<usize>().unwrap();
-    let mode = args[10].clone();    // pc or hit rate
-    let opt_flag: OptimizeVersion = {
-        match args[11].as_ref() {
-            "O0" => OptimizeVersion::O0,
-            "O1" => OptimizeVersion::O1,
-            _ => panic!("opt parse failed!")
+    fn wants_timeline(&self) -> bool {
+        self.timeline.is_some()
+    }
+
+    fn write_users(&mut self, users: &[&SimulationReport]) -> Result<(), SimulatorError> {
+        if let Some(ref mut output) = self.per_user {
+            output.write_with(|output| write_users(users, output))?;
         }
-    };
+        if let Some(ref mut output) = self.timeline {
+            output.write_with(|output| write_timelines(users, output))?;
+        }
+        Ok(())
+    }
 
-    let power_constant_4k_360: Vec<PowerConstants> = read_power_consumption_from_file(Path::new("power_4k_360.json")).unwrap();
-    let power_constant_1080p: Vec<PowerConstants> = read_power_consumption_from_file(Path::new("power_1080p.json")).unwrap();
+    fn write_summary(&mut self, mode: Mode, summary: &SummaryReport) -> Result<(), SimulatorError> {
+        self.summary.write_with(|out| write_summary(mode, summary, out))?;
+        if let Some((ref battery, ref mut output)) = self.battery {
+            output.write_with(|output| {
+                for row in BatteryRow::rows(summary, battery) {
+                    output.rows.write(&row)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
 
-//    compare_each_simulation(&object_result, &dump_file, &cluster_json, threshold, segment, width, height, l2_width, l2_height, &power_constant);
-//    batch_simulation(&object_result, &dump_file, &cluster_json, threshold, segment, &power_constant);
+    fn finish(&mut self) -> Result<(), SimulatorError> {
+        self.summary.finish()?;
+        let battery = self.battery.iter_mut().map(|&mut (_, ref mut output)| output);
+        for output in self.per_user.iter_mut().chain(self.timeline.iter_mut()).chain(battery) {
+            output.finish()?;
+        }
+        Ok(())
+    }
+}
+
+// Simulate every user of every video with every config, then write one summary per video and
+// config to the output of the video. Lines keep the order of `configs` whatever the workers.
+fn run_study(mode: Mode, datasets: &[Dataset], configs: &[SimulatorConfig], workers: usize, outputs: &mut [VideoOutput]) -> Result<(), SimulatorError> {
+    let timelines = outputs.iter().any(VideoOutput::wants_timeline);
+    let results = Sweep::new(datasets, configs).workers(workers).timelines(timelines).run();
+    // jobs are ordered by video, then config, then user, so every config of a video is the next
+    // run of that video's users
+    let mut groups = results.as_slice();
+    for (dataset, output) in datasets.iter().zip(outputs.iter_mut()) {
+        for config in configs {
+            let (group, rest) = groups.split_at(dataset.users.len());
+            groups = rest;
+            let users: Vec<&SimulationReport> = succeeded(dataset, group).into_iter().flatten().collect();
+            output.write_users(&users)?;
+            let summary = SummaryReport::new(SimulationParameters::from(config), &users);
+            output.write_summary(mode, &summary)?;
+        }
+        output.finish()?;
+    }
+    Ok(())
+}
 
-    // for auto.sh
-    let mut user_paths: Vec<DirEntry> = fs::read_dir(&object_result).unwrap().map(|r| r.unwrap()).collect();
-    user_paths.sort_by_key(|dir| dir.path());
-    match mode.as_ref() {
-        "power" => {
-            single_simulate_pc(&user_paths, &dump_file, &cluster_json, threshold, segment,
-                               width, height, l2_width,
-                               l2_height, &power_constant_4k_360, &power_constant_1080p, opt_flag);
+// per-user hit ratios of every config against level one only and against the sensor
+// optimization on top of the same config
+fn run_compare(dataset: &Dataset, configs: &[SimulatorConfig], workers: usize, output: &mut VideoOutput) -> Result<(), SimulatorError> {
+    let mut variants = vec![];
+    for config in configs {
+        variants.push(config.clone());
+        variants.push(SimulatorConfig { level_two: config.level_one, ..config.clone() });
+        variants.push(SimulatorConfig { opt: OptimizeVersion::O1, ..config.clone() });
+    }
+    let results = Sweep::new(std::slice::from_ref(dataset), &variants).workers(workers)
+        .timelines(output.wants_timeline()).run();
+    for group in results.chunks(3 * dataset.users.len()) {
+        let (users, rest) = group.split_at(dataset.users.len());
+        let (base, opt) = rest.split_at(dataset.users.len());
+        let (users, base, opt) = (succeeded(dataset, users), succeeded(dataset, base), succeeded(dataset, opt));
+        for variant in &[&users, &base, &opt] {
+            output.write_users(&variant.iter().flatten().cloned().collect::<Vec<_>>())?;
         }
-        "hit" => {
-            single_simulate_hit(&user_paths, &dump_file, &cluster_json, threshold, segment,
-                                width, height, l2_width,
-                                l2_height, &power_constant_4k_360, &power_constant_1080p, opt_flag);
+        output.summary.write_with(|out| write_compare(&users, &base, &opt, out))?;
+    }
+    output.finish()
+}
+
+fn load_dataset(video: &str, users: &str, tracedump: &str, cluster: &str, fps: Option<f64>) -> Result<Dataset, SimulatorError> {
+    let mut dataset = Dataset::load(video, users, tracedump, cluster)?;
+    if let Some(fps) = fps {
+        dataset.video.set_frame_rate(fps)?;
+    }
+    for e in &dataset.skipped {
+        eprintln!("skipping {}", e);
+    }
+    Ok(dataset)
+}
+
+fn with_trace(mut network: NetworkModel, trace: Option<&String>) -> Result<NetworkModel, SimulatorError> {
+    if let Some(trace) = trace {
+        network.trace = Some(BandwidthTrace::load(trace)?);
+    }
+    Ok(network)
+}
+
+fn run_manifest(path: &str) -> Result<(), SimulatorError> {
+    let manifest = Manifest::load(path)?;
+    let mut base = SimulatorConfig::builder()
+        .power_profile(manifest.power.load()?)
+        .soc_scaling(manifest.soc_scaling.clone())
+        .reproject_level_two(manifest.l2_reprojection);
+    if let Some(ref display) = manifest.display {
+        base = base.display(display.clone());
+    }
+    if let Some(ref ladder) = manifest.ladder {
+        base = base.ladder(ladder.clone());
+    }
+    if let Some(ref abr) = manifest.abr {
+        base = base.abr(abr.abr()?);
+    }
+    if let Some(prefetch) = manifest.prefetch {
+        base = base.prefetch(prefetch);
+    }
+    match (&manifest.network, &manifest.bandwidth_trace) {
+        (Some(network), trace) => base = base.network(with_trace(network.clone(), trace.as_ref())?),
+        (None, Some(_)) => return Err(ConfigError::InvalidNetwork("a bandwidth trace needs a network".to_string()).into()),
+        (None, None) => {}
+    }
+    let configs = manifest.grid.configs(&base)?;
+    let battery = match manifest.battery {
+        Some(ref battery) => Some(battery.clone().checked()?),
+        None => None,
+    };
+
+    let mut datasets = vec![];
+    let mut outputs = vec![];
+    for video in &manifest.videos {
+        datasets.push(load_dataset(&video.power_key, &video.users, &video.tracedump, &video.cluster, video.fps)?);
+        outputs.push(VideoOutput::new(video.output.as_deref(), video.per_user_output.as_deref(),
+                                      video.timeline_output.as_deref(), manifest.format)?
+            .with_battery(battery.as_ref(), video.battery_output.as_deref())?);
+    }
+    let workers = manifest.jobs.unwrap_or_else(default_workers);
+    run_study(manifest.mode, &datasets, &configs, workers, &mut outputs)
+}
+
+fn run_fit(options: &FitOptions) -> Result<(), SimulatorError> {
+    let log = PowerLog::load(&options.logs)?;
+    let power_constants = fit(&log, &read_labels(&options.labels)?)?;
+    let (name, mut out): (&str, Box<dyn Write>) = match options.output {
+        Some(ref path) => (path, Box::new(File::create(path).map_err(|e| SimulatorError::io(path, e))?)),
+        None => ("<stdout>", Box::new(io::stdout())),
+    };
+    write_power_consumption(&mut out, &power_constants)
+        .map_err(|e| SimulatorError::json(name, e))?;
+    writeln!(out).map_err(|e| SimulatorError::io(name, e))
+}
+
+fn run(command: Command) -> Result<(), SimulatorError> {
+    let options = match command {
+        Command::Simulate(_, ref options) | Command::Compare(ref options) | Command::Sweep(ref options) => options,
+        Command::Run(ref manifest) => return run_manifest(manifest),
+        Command::Fit(ref options) => return run_fit(options),
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            return Ok(());
         }
-        _ => assert!(false),
+    };
+
+    let mut base = SimulatorConfig::builder().power_profile(options.power.load()?);
+    if let Some(ref soc_scaling) = options.soc_scaling {
+        base = base.soc_scaling(SocScaling::from_arg(soc_scaling)?);
+    }
+    if let Some(reproject_level_two) = options.reproject_level_two {
+        base = base.reproject_level_two(reproject_level_two);
+    }
+    if let Some(ref display) = options.display {
+        base = base.display(DisplayModel::load(display)?);
+    }
+    if let Some(ref ladder) = options.ladder {
+        base = base.ladder(BitrateLadder::load(ladder)?);
+    }
+    if let Some(ref abr) = options.abr {
+        base = base.abr(AbrModel::load(abr)?.abr()?);
+    }
+    if let Some(ref prefetch) = options.prefetch {
+        base = base.prefetch(Prefetch::load(prefetch)?);
+    }
+    if let Some(ref network) = options.network {
+        base = base.network(with_trace(NetworkModel::load(network)?, options.bandwidth_trace.as_ref())?);
+    }
+    let configs = options.grid.configs(&base)?;
+    let datasets = [load_dataset(&options.video, &options.users, &options.tracedump, &options.cluster, options.fps)?];
+    let workers = options.jobs.unwrap_or_else(default_workers);
+
+    let battery = match options.battery {
+        Some(ref battery) => Some(Battery::load(battery)?),
+        None => None,
+    };
+    let mut output = VideoOutput::new(None, options.per_user.as_deref(), options.timeline.as_deref(), options.format)?
+        .with_battery(battery.as_ref(), options.battery_output.as_deref())?;
+    match command {
+        Command::Simulate(mode, _) => run_study(mode, &datasets, &configs, workers, std::slice::from_mut(&mut output)),
+        Command::Sweep(_) => run_study(Mode::Power, &datasets, &configs, workers, std::slice::from_mut(&mut output)),
+        Command::Compare(_) => run_compare(&datasets[0], &configs, workers, &mut output),
+        Command::Run(_) | Command::Fit(_) | Command::Help => unreachable!(),
+    }
+}
+
+#[allow(dead_code)]
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = cli::parse(&args).unwrap_or_else(|e| {
+        eprintln!("error: {}\n\n{}", e, cli::USAGE);
+        process::exit(2);
+    });
+    if let Err(e) = run(command) {
+        eprintln!("error: {}", e);
+        process::exit(1);
     }
 }
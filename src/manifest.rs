@@ -0,0 +1,95 @@
+use cli::Mode;
+use config::ParameterGrid;
+use error::SimulatorError;
+use output::Format;
+use battery::Battery;
+use abr::AbrModel;
+use prefetch::Prefetch;
+use ladder::BitrateLadder;
+use network::NetworkModel;
+use power::{DisplayModel, PowerSource, SocScaling};
+
+use std::fs::File;
+use std::path::Path;
+
+extern crate serde;
+extern crate serde_json;
+
+// One video of a study. Paths are relative to the working directory, like the arguments of the
+// command line.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct VideoEntry {
+    pub users: String,
+    pub tracedump: String,
+    pub cluster: String,
+    // name of the video in the power constants and in the rows, e.g. `Elephant`
+    pub power_key: String,
+    // frames per second, 30 when left out
+    #[serde(default)]
+    pub fps: Option<f64>,
+    // file the results of this video are written to, stdout when left out
+    #[serde(default)]
+    pub output: Option<String>,
+    // file the report of every user is written to, left out by default
+    #[serde(default)]
+    pub per_user_output: Option<String>,
+    // file the power of every frame of every user is written to, left out by default
+    #[serde(default)]
+    pub timeline_output: Option<String>,
+    // file the discharge of the battery is written to, needs `battery`
+    #[serde(default)]
+    pub battery_output: Option<String>,
+}
+
+// A whole study: every video is simulated with every point of `grid`. See auto_pc.json.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Manifest {
+    #[serde(default = "default_mode")]
+    pub mode: Mode,
+    #[serde(default)]
+    pub power: PowerSource,
+    #[serde(default)]
+    pub grid: ParameterGrid,
+    #[serde(default)]
+    pub soc_scaling: SocScaling,
+    #[serde(default = "default_l2_reprojection")]
+    pub l2_reprojection: bool,
+    // display panel, no display power when left out
+    #[serde(default)]
+    pub display: Option<DisplayModel>,
+    #[serde(default)]
+    pub network: Option<NetworkModel>,
+    #[serde(default)]
+    pub ladder: Option<BitrateLadder>,
+    #[serde(default)]
+    pub abr: Option<AbrModel>,
+    #[serde(default)]
+    pub prefetch: Option<Prefetch>,
+    // throughput trace file replacing the bandwidth of the network
+    #[serde(default)]
+    pub bandwidth_trace: Option<String>,
+    #[serde(default)]
+    pub battery: Option<Battery>,
+    pub videos: Vec<VideoEntry>,
+    #[serde(default)]
+    pub format: Format,
+    // worker threads, one per cpu when left out
+    #[serde(default)]
+    pub jobs: Option<usize>,
+}
+
+fn default_mode() -> Mode {
+    Mode::Power
+}
+
+fn default_l2_reprojection() -> bool {
+    true
+}
+
+impl Manifest {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SimulatorError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+        serde_json::from_reader(file).map_err(|e| SimulatorError::json(&name, e))
+    }
+}
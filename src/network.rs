@@ -0,0 +1,451 @@
+use abr::{Abr, AbrState};
+use config::ConfigError;
+use dataset::{parse_field, read_lines};
+use error::SimulatorError;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+extern crate serde_json;
+
+// Bitrates of one video in Mbit/s: `normal` for the 1920x1080 video, which level one and two
+// scale by their area, and `panorama` for the full 360 video.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct VideoBitrate {
+    pub normal: f64,
+    pub panorama: f64,
+}
+
+// Power of the radio in the unit of the power constants: `active` while transferring, `tail` for
+// up to `tail_time` seconds after a transfer, then `idle` until the next one.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct RadioPower {
+    pub active: f64,
+    pub tail: f64,
+    pub idle: f64,
+    pub tail_time: f64,
+}
+
+// Transfers of every segment over a link of `bandwidth` Mbit/s with a round trip of `rtt`
+// milliseconds. Written in json like
+// `{"bandwidth": 50, "rtt": 20, "bitrate": {"normal": 8, "panorama": 40}, "videos": {"Paris": {...}},
+//   "radio": {"active": 800, "tail": 400, "idle": 20, "tail_time": 0.2}}`
+// where `videos` overrides `bitrate` for some videos. A bandwidth trace replaces `bandwidth`. With a
+// `buffer`, segments are fetched ahead of playback and a level three fallback waits for its data.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NetworkModel {
+    pub bandwidth: f64,
+    pub rtt: f64,
+    pub bitrate: VideoBitrate,
+    #[serde(default)]
+    pub videos: HashMap<String, VideoBitrate>,
+    pub radio: RadioPower,
+    #[serde(default)]
+    pub buffer: Option<PlaybackBuffer>,
+    #[serde(skip)]
+    pub trace: Option<BandwidthTrace>,
+}
+
+// Buffer of `segments` segments fetched ahead of playback, which starts once `startup` seconds of
+// video have arrived, at least the first segment.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct PlaybackBuffer {
+    pub segments: usize,
+    #[serde(default)]
+    pub startup: f64,
+}
+
+// One segment of `duration` seconds to play: `bytes` fetched ahead and level three fetched when
+// its frame `fallback_at` seconds into the segment plays. `fallback_bytes` are the bytes of its
+// level three frames at every quality, best first, and empty when it does not fall back. A
+// `prefetched` segment fetches its level three ahead with the rest instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentFetch {
+    pub duration: f64,
+    pub bytes: f64,
+    pub fallback_at: f64,
+    pub fallback_bytes: Vec<f64>,
+    pub prefetched: bool,
+}
+
+// Playback of the segments, through the buffer or as they are requested. `transfers` are sent one
+// after the other, each with the position of its segment, and playback ends at `end`. `qualities`
+// holds the level three quality of every segment falling back.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Playback {
+    pub startup_latency: f64,
+    pub stalls: usize,
+    pub stall_time: f64,
+    pub end: f64,
+    pub transfers: Vec<(usize, Transfer)>,
+    pub qualities: Vec<Option<usize>>,
+}
+
+// transfers the throughput estimate averages over
+const THROUGHPUT_SAMPLES: usize = 5;
+
+// harmonic mean of the throughput of the last transfers, in bytes per second
+#[derive(Debug, Default)]
+struct Throughput {
+    samples: Vec<f64>,
+}
+
+impl Throughput {
+    fn add(&mut self, bytes: f64, seconds: f64) {
+        if bytes > 0.0 && seconds > 0.0 {
+            self.samples.push(bytes / seconds);
+            if self.samples.len() > THROUGHPUT_SAMPLES {
+                self.samples.remove(0);
+            }
+        }
+    }
+
+    fn estimate(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(self.samples.len() as f64 / self.samples.iter().map(|s| 1.0 / s).sum::<f64>())
+    }
+}
+
+// Quality of the level three frames of the segment at `position` and their bytes: the best one
+// without a policy. They play from the fallback on, or over the whole segment when prefetched.
+fn fallback_quality(abr: Option<&Abr>, position: usize, segment: &SegmentFetch, buffer: f64, throughput: &Throughput,
+                    last: Option<usize>) -> (usize, f64) {
+    let quality = match abr {
+        Some(abr) => abr.choose(&AbrState {
+            segment: position,
+            bytes: &segment.fallback_bytes,
+            duration: if segment.prefetched { segment.duration } else { segment.duration - segment.fallback_at },
+            buffer,
+            throughput: throughput.estimate(),
+            last,
+        }),
+        None => 0,
+    };
+    (quality, segment.fallback_bytes[quality])
+}
+
+// bytes of one delivery opportunity of a mahimahi trace
+const MAHIMAHI_PACKET: f64 = 1500.0;
+
+// Bytes the link delivers over time, replayed in a loop. Read from lines of `time_ms bytes`, or of
+// `time_ms` alone as in mahimahi traces where every line delivers one packet of 1500 bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandwidthTrace {
+    // time in seconds and the bytes delivered up to it, within one period
+    deliveries: Vec<(f64, f64)>,
+    period: f64,
+}
+
+// Time the radio spent in each state over a run and the bytes it received. Transfers done after
+// their deadline are late, `late_fallbacks` of them carried level three, and `late_time` sums how
+// long after. With a buffer, playback starts after `startup_latency` seconds and stalls `stalls`
+// times for `stall_time` seconds in total.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize)]
+pub struct NetworkReport {
+    pub bytes: f64,
+    pub active_time: f64,
+    pub tail_time: f64,
+    pub idle_time: f64,
+    pub late_transfers: f64,
+    pub late_fallbacks: f64,
+    pub late_time: f64,
+    pub startup_latency: f64,
+    pub stalls: f64,
+    pub stall_time: f64,
+}
+
+// One transfer: `bytes` requested `request` seconds into the video and needed by `deadline`.
+// `fallback` transfers carry level three.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transfer {
+    pub request: f64,
+    pub deadline: f64,
+    pub bytes: f64,
+    pub fallback: bool,
+}
+
+impl BandwidthTrace {
+    // `(time, bytes)` deliveries in seconds, the trace repeating after the last one
+    pub fn new(mut deliveries: Vec<(f64, f64)>) -> Result<Self, ConfigError> {
+        if let Some(d) = deliveries.iter().find(|d| !(d.0 >= 0.0 && d.0.is_finite() && d.1 >= 0.0 && d.1.is_finite())) {
+            return Err(ConfigError::InvalidNetwork(format!("delivery of {} bytes at {}s", d.1, d.0)));
+        }
+        deliveries.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let period = deliveries.last().map_or(0.0, |d| d.0);
+        let mut total = 0.0;
+        for delivery in &mut deliveries {
+            total += delivery.1;
+            delivery.1 = total;
+        }
+        if !(period > 0.0 && total > 0.0) {
+            return Err(ConfigError::InvalidNetwork("the bandwidth trace delivers nothing".to_string()));
+        }
+        Ok(BandwidthTrace { deliveries, period })
+    }
+
+    pub fn load(path: &str) -> Result<Self, SimulatorError> {
+        let mut deliveries = vec![];
+        for (line_no, line) in read_lines(path)?.iter().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let time = parse_field::<f64>(&fields, 0, "time", path, line_no + 1)?;
+            let bytes = if fields.len() > 1 {
+                parse_field::<f64>(&fields, 1, "bytes", path, line_no + 1)?
+            } else {
+                MAHIMAHI_PACKET
+            };
+            deliveries.push((time / 1000.0, bytes));
+        }
+        Ok(BandwidthTrace::new(deliveries)?)
+    }
+
+    fn total(&self) -> f64 {
+        self.deliveries[self.deliveries.len() - 1].1
+    }
+
+    // bytes delivered from the start of the trace up to `time`
+    pub fn delivered(&self, time: f64) -> f64 {
+        let periods = (time / self.period).floor();
+        let within = time - periods * self.period;
+        let i = self.deliveries.partition_point(|d| d.0 <= within);
+        periods * self.total() + if i > 0 { self.deliveries[i - 1].1 } else { 0.0 }
+    }
+
+    // first time the trace has delivered `bytes` since `start`
+    pub fn arrival(&self, start: f64, bytes: f64) -> f64 {
+        let target = self.delivered(start) + bytes;
+        if bytes <= 0.0 {
+            return start;
+        }
+        let periods = (target / self.total()).ceil() - 1.0;
+        let rest = target - periods * self.total();
+        let i = self.deliveries.partition_point(|d| d.1 < rest).min(self.deliveries.len() - 1);
+        (periods * self.period + self.deliveries[i].0).max(start)
+    }
+}
+
+impl NetworkModel {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SimulatorError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+        serde_json::from_reader(file).map_err(|e| SimulatorError::json(&name, e))
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.bandwidth > 0.0 && self.bandwidth.is_finite()) {
+            return Err(ConfigError::InvalidNetwork(format!("bandwidth {} is not positive", self.bandwidth)));
+        }
+        if !(self.rtt >= 0.0 && self.rtt.is_finite()) {
+            return Err(ConfigError::InvalidNetwork(format!("round trip {} is negative", self.rtt)));
+        }
+        for bitrate in Some(&self.bitrate).into_iter().chain(self.videos.values()) {
+            if !(bitrate.normal >= 0.0 && bitrate.panorama >= 0.0) {
+                return Err(ConfigError::InvalidNetwork("negative bitrate".to_string()));
+            }
+        }
+        if let Some(buffer) = self.buffer {
+            if buffer.segments == 0 {
+                return Err(ConfigError::InvalidNetwork("the buffer holds no segment".to_string()));
+            }
+            if !(buffer.startup >= 0.0 && buffer.startup.is_finite()) {
+                return Err(ConfigError::InvalidNetwork(format!("startup {} is negative", buffer.startup)));
+            }
+        }
+        let radio = &self.radio;
+        if !(radio.active >= 0.0 && radio.tail >= 0.0 && radio.idle >= 0.0 && radio.tail_time >= 0.0) {
+            return Err(ConfigError::InvalidNetwork("negative radio power or tail time".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn bitrate(&self, video: &str) -> VideoBitrate {
+        self.videos.get(video).cloned().unwrap_or(self.bitrate)
+    }
+
+    // time `bytes` requested at `start` are received, one round trip included
+    pub fn arrival(&self, start: f64, bytes: f64) -> f64 {
+        let start = start + self.rtt / 1000.0;
+        match self.trace {
+            Some(ref trace) => trace.arrival(start, bytes),
+            None => start + bytes * 8.0 / (self.bandwidth * 1e6),
+        }
+    }
+
+    // send `bytes` requested at `request` once the link is free, returns when it was sent and done
+    fn send(&self, request: f64, bytes: f64, link_free: &mut f64) -> (f64, f64) {
+        let start = request.max(*link_free);
+        *link_free = self.arrival(start, bytes);
+        (start, *link_free)
+    }
+
+    // Plays the segments as they are requested, each when it starts playing with its level three
+    // frames, which the policy chooses the quality of without any buffer.
+    pub fn stream(&self, segments: &[SegmentFetch], abr: Option<&Abr>) -> Playback {
+        let mut playback = Playback::default();
+        let mut link_free = 0.0;
+        let mut throughput = Throughput::default();
+        let mut last = None;
+        for (i, segment) in segments.iter().enumerate() {
+            let request = playback.end;
+            let mut transfer = Transfer { request, deadline: request + segment.duration, bytes: segment.bytes, fallback: false };
+            let mut quality = None;
+            if !segment.fallback_bytes.is_empty() {
+                let (chosen, bytes) = fallback_quality(abr, i, segment, 0.0, &throughput, last);
+                transfer.bytes += bytes;
+                transfer.fallback = !segment.prefetched;
+                quality = Some(chosen);
+                last = quality;
+            }
+            let (sent, done) = self.send(request, transfer.bytes, &mut link_free);
+            throughput.add(transfer.bytes, done - sent);
+            playback.transfers.push((i, transfer));
+            playback.qualities.push(quality);
+            playback.end += segment.duration;
+        }
+        playback
+    }
+
+    // Plays the segments through the buffer. The next segment is fetched once the buffer has room
+    // for it, and a fallback as soon as its frame plays, before any segment requested later, at
+    // the quality the policy chooses, or with the segment when it is prefetched. Playback stalls
+    // when a segment or a fallback is not there in time.
+    pub fn play(&self, buffer: &PlaybackBuffer, segments: &[SegmentFetch], abr: Option<&Abr>) -> Playback {
+        let mut playback = Playback { qualities: vec![None; segments.len()], ..Playback::default() };
+        if segments.is_empty() {
+            return playback;
+        }
+        // a buffer holds at least the segment playing
+        let size = buffer.segments.max(1);
+        let mut buffered = 0.0;
+        let startup_segment = segments.iter()
+            .position(|s| {
+                buffered += s.duration;
+                buffered >= buffer.startup
+            })
+            .unwrap_or(segments.len() - 1)
+            .min(size - 1);
+
+        let mut link_free = 0.0;
+        let mut throughput = Throughput::default();
+        let mut last = None;
+        let mut arrivals: Vec<f64> = vec![];
+        let mut ends: Vec<f64> = vec![];
+        while ends.len() < segments.len() {
+            let (fetched, played) = (arrivals.len(), ends.len());
+            // when the next segment may be requested, none once every segment is fetched or while
+            // the buffer is full
+            let request_at = if fetched < segments.len() && fetched < played + size {
+                Some(if fetched < size { 0.0 } else { ends[fetched - size] })
+            } else {
+                None
+            };
+            if played < fetched && (played > 0 || fetched > startup_segment) {
+                let segment = &segments[played];
+                let start = if played == 0 { arrivals[startup_segment] } else { ends[played - 1].max(arrivals[played]) };
+                let request = start + segment.fallback_at;
+                let fallback = !segment.fallback_bytes.is_empty() && !segment.prefetched;
+                if !(fallback && request_at.is_some_and(|r| r < request)) {
+                    if played == 0 {
+                        playback.startup_latency = start;
+                    } else if arrivals[played] > ends[played - 1] {
+                        playback.stalls += 1;
+                        playback.stall_time += arrivals[played] - ends[played - 1];
+                    }
+                    let mut end = start + segment.duration;
+                    if fallback {
+                        // video buffered ahead, the segments arrived after this one
+                        let buffered: f64 = (played + 1..fetched).filter(|&i| arrivals[i] <= request)
+                            .map(|i| segments[i].duration)
+                            .sum();
+                        let (quality, bytes) = fallback_quality(abr, played, segment, buffered, &throughput, last);
+                        playback.qualities[played] = Some(quality);
+                        last = Some(quality);
+                        let (sent, done) = self.send(request, bytes, &mut link_free);
+                        throughput.add(bytes, done - sent);
+                        let transfer = Transfer { request: sent, deadline: request, bytes, fallback };
+                        playback.transfers.push((played, transfer));
+                        if done > request {
+                            playback.stalls += 1;
+                            playback.stall_time += done - request;
+                            end += done - request;
+                        }
+                    }
+                    ends.push(end);
+                    continue;
+                }
+            }
+            // Only reached when nothing can play, and then `request_at` is set: once every segment is
+            // fetched the next one to play has arrived, the startup segment with it, and a full
+            // buffer holds the next one to play, past the startup segment which fits in the buffer.
+            let request = request_at.expect("the buffer has room while no segment can play");
+            let segment = &segments[fetched];
+            let mut bytes = segment.bytes;
+            if segment.prefetched && !segment.fallback_bytes.is_empty() {
+                let buffered: f64 = (played..fetched).filter(|&i| arrivals[i] <= request)
+                    .map(|i| segments[i].duration)
+                    .sum();
+                let (quality, fallback_bytes) = fallback_quality(abr, fetched, segment, buffered, &throughput, last);
+                playback.qualities[fetched] = Some(quality);
+                last = Some(quality);
+                bytes += fallback_bytes;
+            }
+            let (sent, done) = self.send(request, bytes, &mut link_free);
+            throughput.add(bytes, done - sent);
+            arrivals.push(done);
+            let transfer = Transfer { request: sent, deadline: 0.0, bytes, fallback: false };
+            playback.transfers.push((fetched, transfer));
+        }
+        // a segment is needed when the one before it ends
+        for &mut (i, ref mut transfer) in &mut playback.transfers {
+            if !transfer.fallback {
+                transfer.deadline = if i == 0 { playback.startup_latency } else { ends[i - 1] };
+            }
+        }
+        playback.end = ends[ends.len() - 1];
+        playback
+    }
+
+    // Radio states over `duration` seconds with the transfers sent one after the other, each as
+    // soon as it is requested and the previous one is done, at the bandwidth or along the trace.
+    // Returns the time in every state and the radio energy of every transfer: its active time and
+    // the tail and idle time up to the next one, the idle time before the first transfer included.
+    pub fn simulate(&self, transfers: &[Transfer], duration: f64) -> (NetworkReport, Vec<f64>) {
+        let radio = &self.radio;
+        let mut report = NetworkReport::default();
+        let mut energies = vec![0.0; transfers.len()];
+        // energy of the gap between two transfers, tail first
+        let gap = |report: &mut NetworkReport, gap: f64, after_transfer: bool| {
+            let tail = if after_transfer { gap.min(radio.tail_time) } else { 0.0 };
+            report.tail_time += tail;
+            report.idle_time += gap - tail;
+            tail * radio.tail + (gap - tail) * radio.idle
+        };
+
+        let mut free = 0.0;
+        for (i, transfer) in transfers.iter().enumerate() {
+            let start = transfer.request.max(free);
+            let waited = gap(&mut report, start - free, i > 0);
+            energies[i.saturating_sub(1)] += waited;
+            let done = self.arrival(start, transfer.bytes);
+            let active = done - start;
+            report.bytes += transfer.bytes;
+            report.active_time += active;
+            energies[i] += active * radio.active;
+            if done > transfer.deadline {
+                report.late_transfers += 1.0;
+                report.late_fallbacks += if transfer.fallback { 1.0 } else { 0.0 };
+                report.late_time += done - transfer.deadline;
+            }
+            free = done;
+        }
+        if let Some(last) = energies.last_mut() {
+            *last += gap(&mut report, (duration - free).max(0.0), true);
+        }
+        (report, energies)
+    }
+}
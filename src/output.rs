@@ -0,0 +1,136 @@
+use serde::de::{self, Deserialize, Deserializer};
+use serde::Serialize;
+
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+extern crate serde_json;
+
+// How result rows are written. `Plain` is the historical space separated line without a header,
+// see the README for its columns.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Format {
+    #[default]
+    Plain,
+    Csv,
+    Jsonl,
+    Pretty,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Format::Plain),
+            "csv" => Ok(Format::Csv),
+            "jsonl" => Ok(Format::Jsonl),
+            "pretty" => Ok(Format::Pretty),
+            _ => Err(format!("expected plain, csv, jsonl or pretty, got `{}`", s)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Format {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Format::Plain => "plain",
+            Format::Csv => "csv",
+            Format::Jsonl => "jsonl",
+            Format::Pretty => "pretty",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// A result that can be written as one row. `values` lines up with `columns`; json rows use the
+// `Serialize` impl instead.
+pub trait Row: Serialize {
+    fn columns() -> &'static [&'static str];
+    fn values(&self) -> Vec<String>;
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Writes rows of one type in a `Format`. Csv starts with a header line; pretty tables are kept
+// until `finish` so every column can be aligned.
+pub struct RowWriter<W: Write> {
+    format: Format,
+    out: W,
+    header_written: bool,
+    table: Vec<Vec<String>>,
+}
+
+impl<W: Write> RowWriter<W> {
+    pub fn new(format: Format, out: W) -> Self {
+        RowWriter {
+            format,
+            out,
+            header_written: false,
+            table: vec![],
+        }
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    // the underlying writer, for lines that are not rows
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    pub fn write<R: Row>(&mut self, row: &R) -> io::Result<()> {
+        match self.format {
+            Format::Plain => writeln!(self.out, "{}", row.values().join(" ")),
+            Format::Csv => {
+                if !self.header_written {
+                    writeln!(self.out, "{}", R::columns().join(","))?;
+                    self.header_written = true;
+                }
+                let values: Vec<String> = row.values().iter().map(|v| csv_field(v)).collect();
+                writeln!(self.out, "{}", values.join(","))
+            }
+            Format::Jsonl => {
+                serde_json::to_writer(&mut self.out, row)?;
+                writeln!(self.out)
+            }
+            Format::Pretty => {
+                if self.table.is_empty() {
+                    self.table.push(R::columns().iter().map(|c| c.to_string()).collect());
+                }
+                self.table.push(row.values());
+                Ok(())
+            }
+        }
+    }
+
+    // write what is still buffered and flush
+    pub fn finish(&mut self) -> io::Result<()> {
+        if !self.table.is_empty() {
+            let columns = self.table[0].len();
+            let widths: Vec<usize> = (0..columns)
+                .map(|i| self.table.iter().map(|row| row[i].len()).max().unwrap_or(0))
+                .collect();
+            for row in self.table.drain(..) {
+                let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, &width)| format!("{:<1$}", cell, width)).collect();
+                writeln!(self.out, "{}", cells.join("  ").trim_end())?;
+            }
+        }
+        self.out.flush()
+    }
+}
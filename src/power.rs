@@ -0,0 +1,405 @@
+use constants;
+use ds::Size;
+use error::SimulatorError;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+extern crate serde;
+extern crate serde_json;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PowerConstants {
+    pub(crate) name: String,
+    pub(crate) value: f64,
+}
+
+impl PowerConstants {
+    pub fn new(name: &str, value: f64) -> Self {
+        PowerConstants {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+pub fn read_power_consumption_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<PowerConstants>, SimulatorError> {
+    let name = path.as_ref().to_string_lossy().into_owned();
+    let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+    let u = serde_json::from_reader(file).map_err(|e| SimulatorError::json(&name, e))?;
+    Ok(u)
+}
+
+// in the format `read_power_consumption_from_file` reads
+pub fn write_power_consumption<W: Write>(out: W, power_constants: &[PowerConstants]) -> serde_json::Result<()> {
+    serde_json::to_writer_pretty(out, power_constants)
+}
+
+// Power constants of one video with one encoding, for a 1280x720 viewport. `decode` is only
+// known when it was measured on its own.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct VideoPower {
+    pub wifi: f64,
+    pub soc: f64,
+    pub decode: Option<f64>,
+}
+
+// Constants named `<Video>_WIFI`, `<Video>_SOC` and `<Video>_DECODE`, grouped by video.
+fn group_by_video(file: &str, power_constants: Vec<PowerConstants>) -> Result<HashMap<String, VideoPower>, SimulatorError> {
+    let mut wifi = HashMap::new();
+    let mut soc = HashMap::new();
+    let mut decode = HashMap::new();
+    for constant in power_constants {
+        let (video, kind) = match constant.name.rfind('_') {
+            Some(pos) => (constant.name[..pos].to_string(), &constant.name[pos + 1..]),
+            None => return Err(SimulatorError::UnknownPowerConstant { file: file.to_string(), name: constant.name.clone() }),
+        };
+        match kind {
+            "WIFI" => wifi.insert(video, constant.value),
+            "SOC" => soc.insert(video, constant.value),
+            "DECODE" => decode.insert(video, constant.value),
+            _ => return Err(SimulatorError::UnknownPowerConstant { file: file.to_string(), name: constant.name.clone() }),
+        };
+    }
+
+    let mut videos: Vec<&String> = wifi.keys().chain(soc.keys()).chain(decode.keys()).collect();
+    videos.sort();
+    videos.dedup();
+    let mut grouped = HashMap::new();
+    for video in videos {
+        let get = |constants: &HashMap<String, f64>, kind: &str| {
+            constants.get(video).cloned()
+                .ok_or_else(|| SimulatorError::MissingPowerConstant { name: format!("{}_{}", video, kind) })
+        };
+        grouped.insert(video.clone(), VideoPower {
+            wifi: get(&wifi, "WIFI")?,
+            soc: get(&soc, "SOC")?,
+            decode: decode.get(video).cloned(),
+        });
+    }
+    Ok(grouped)
+}
+
+// the json layout of a profile, one list of named constants per encoding
+#[derive(Deserialize)]
+struct PowerProfileFile {
+    power_360: Vec<PowerConstants>,
+    power_1080p: Vec<PowerConstants>,
+}
+
+// Power constants measured on one device: `power_360` with the 4k 360 video and `power_1080p`
+// with the normal 1080p video, in the format of power_4k_360.json and power_1080p.json, keyed by
+// the name of the video, e.g. `Elephant`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PowerProfile {
+    power_360: HashMap<String, VideoPower>,
+    power_1080p: HashMap<String, VideoPower>,
+}
+
+impl PowerProfile {
+    pub fn new(power_360: Vec<PowerConstants>, power_1080p: Vec<PowerConstants>) -> Result<Self, SimulatorError> {
+        Ok(PowerProfile {
+            power_360: group_by_video("power_360", power_360)?,
+            power_1080p: group_by_video("power_1080p", power_1080p)?,
+        })
+    }
+
+    pub fn from_files<P: AsRef<Path>, Q: AsRef<Path>>(power_360: P, power_1080p: Q) -> Result<Self, SimulatorError> {
+        let name_360 = power_360.as_ref().to_string_lossy().into_owned();
+        let name_1080p = power_1080p.as_ref().to_string_lossy().into_owned();
+        Ok(PowerProfile {
+            power_360: group_by_video(&name_360, read_power_consumption_from_file(power_360)?)?,
+            power_1080p: group_by_video(&name_1080p, read_power_consumption_from_file(power_1080p)?)?,
+        })
+    }
+
+    // pick the profile of `device` from a file bundling several devices, e.g.
+    // `{"tx2": {"power_360": [...], "power_1080p": [...]}, "pixel2": {...}}`
+    pub fn from_bundle<P: AsRef<Path>>(path: P, device: &str) -> Result<Self, SimulatorError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+        let mut bundle: HashMap<String, PowerProfileFile> = serde_json::from_reader(file)
+            .map_err(|e| SimulatorError::json(&name, e))?;
+        let profile = bundle.remove(device)
+            .ok_or_else(|| SimulatorError::UnknownDevice { file: name.clone(), device: device.to_string() })?;
+        Ok(PowerProfile {
+            power_360: group_by_video(&name, profile.power_360)?,
+            power_1080p: group_by_video(&name, profile.power_1080p)?,
+        })
+    }
+
+    fn find<'a>(videos: &'a HashMap<String, VideoPower>, video: &str) -> Result<&'a VideoPower, SimulatorError> {
+        videos.get(video).ok_or_else(|| SimulatorError::UnknownVideo { video: video.to_string() })
+    }
+
+    // constants of `video` measured with the 4k 360 encoding
+    pub fn get_360(&self, video: &str) -> Result<&VideoPower, SimulatorError> {
+        Self::find(&self.power_360, video)
+    }
+
+    // constants of `video` measured with the 1080p encoding
+    pub fn get_1080p(&self, video: &str) -> Result<&VideoPower, SimulatorError> {
+        Self::find(&self.power_1080p, video)
+    }
+}
+
+// where the power constants of a run come from, either one file per video type or a bundle of
+// several devices
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum PowerSource {
+    Files { power_360: String, power_1080p: String },
+    Bundle { path: String, device: String },
+}
+
+impl Default for PowerSource {
+    fn default() -> Self {
+        PowerSource::Files {
+            power_360: "power_4k_360.json".to_string(),
+            power_1080p: "power_1080p.json".to_string(),
+        }
+    }
+}
+
+impl PowerSource {
+    pub fn load(&self) -> Result<PowerProfile, SimulatorError> {
+        match *self {
+            PowerSource::Files { ref power_360, ref power_1080p } => PowerProfile::from_files(power_360, power_1080p),
+            PowerSource::Bundle { ref path, ref device } => PowerProfile::from_bundle(path, device),
+        }
+    }
+}
+
+// Brightness of the panel from `from` seconds into the video until the next step, in [0, 1].
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct BrightnessStep {
+    pub from: f64,
+    pub level: f64,
+}
+
+// Either one level for the whole video or steps over time, e.g. `0.8` or
+// `[{"from": 0, "level": 0.8}, {"from": 30, "level": 0.4}]`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Brightness {
+    Constant(f64),
+    Profile(Vec<BrightnessStep>),
+}
+
+impl Brightness {
+    // level at `time` seconds, the first step holds before it starts
+    pub fn level(&self, time: f64) -> f64 {
+        match *self {
+            Brightness::Constant(level) => level,
+            Brightness::Profile(ref steps) => {
+                steps.iter().rev().find(|s| s.from <= time).or(steps.first()).map_or(0.0, |s| s.level)
+            }
+        }
+    }
+}
+
+fn default_refresh_rate() -> f64 {
+    60.0
+}
+
+// Power of the display panel, drawn for every frame whatever cache level it comes from. The panel
+// draws `static_power` when dark and `pixel_power` more per megapixel at full brightness and
+// 60 Hz, both in the unit of the power constants. Written in json like
+// `{"panel": "2880x1600", "refresh_rate": 90, "brightness": 0.8, "static_power": 150, "pixel_power": 120}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DisplayModel {
+    pub panel: Size,
+    #[serde(default = "default_refresh_rate")]
+    pub refresh_rate: f64,
+    pub brightness: Brightness,
+    pub static_power: f64,
+    pub pixel_power: f64,
+}
+
+impl DisplayModel {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SimulatorError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+        let mut display: DisplayModel = serde_json::from_reader(file).map_err(|e| SimulatorError::json(&name, e))?;
+        display.sort();
+        Ok(display)
+    }
+
+    // the brightness steps, sorted by time
+    pub(crate) fn steps(&self) -> &[BrightnessStep] {
+        match self.brightness {
+            Brightness::Constant(_) => &[],
+            Brightness::Profile(ref steps) => steps,
+        }
+    }
+
+    pub(crate) fn sort(&mut self) {
+        if let Brightness::Profile(ref mut steps) = self.brightness {
+            steps.sort_by(|a, b| a.from.total_cmp(&b.from));
+        }
+    }
+
+    // power of the panel `time` seconds into the video
+    pub fn power(&self, time: f64) -> f64 {
+        let megapixels = self.panel.width as f64 * self.panel.height as f64 / 1e6;
+        self.static_power + self.pixel_power * megapixels * self.brightness.level(time) * self.refresh_rate / 60.0
+    }
+}
+
+// Power of a run in watts and the energy it took in joules over the simulated frames.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize)]
+pub struct Energy {
+    // seconds of video simulated
+    pub duration: f64,
+    pub wifi_watts: f64,
+    pub soc_watts: f64,
+    pub display_watts: f64,
+    pub wifi_joules: f64,
+    pub soc_joules: f64,
+    pub display_joules: f64,
+}
+
+impl Energy {
+    // powers in the unit of the power constants, averaged over `frames` frames
+    pub fn new(wifi_pc: f64, soc_pc: f64, display_pc: f64, frames: usize, frame_rate: f64) -> Self {
+        let duration = frames as f64 / frame_rate;
+        let wifi_watts = wifi_pc * constants::WATTS_PER_POWER_UNIT;
+        let soc_watts = soc_pc * constants::WATTS_PER_POWER_UNIT;
+        let display_watts = display_pc * constants::WATTS_PER_POWER_UNIT;
+        Energy {
+            duration,
+            wifi_watts,
+            soc_watts,
+            display_watts,
+            wifi_joules: wifi_watts * duration,
+            soc_joules: soc_watts * duration,
+            display_joules: display_watts * duration,
+        }
+    }
+
+    pub fn joules(&self) -> f64 {
+        self.wifi_joules + self.soc_joules + self.display_joules
+    }
+}
+
+// SoC power split by what it is spent on. Without a `<video>_DECODE` constant decoding is
+// counted as rendering.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize)]
+pub struct SocComponents {
+    pub decode: f64,
+    pub render: f64,
+    pub reproject: f64,
+}
+
+impl SocComponents {
+    pub fn total(&self) -> f64 {
+        self.decode + self.render + self.reproject
+    }
+
+    pub(crate) fn scale(&self, factor: f64) -> SocComponents {
+        SocComponents {
+            decode: self.decode * factor,
+            render: self.render * factor,
+            reproject: self.reproject * factor,
+        }
+    }
+
+    pub(crate) fn add(&self, other: &SocComponents) -> SocComponents {
+        SocComponents {
+            decode: self.decode + other.decode,
+            render: self.render + other.render,
+            reproject: self.reproject + other.reproject,
+        }
+    }
+}
+
+// SoC power measured for a tile of `pixels` pixels, relative to the 1280x720 tile of the power
+// constants.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct ScalePoint {
+    pub pixels: f64,
+    pub factor: f64,
+}
+
+// How the decode and render power of the SoC, measured with a 1280x720 tile, scales to the size
+// of the level one and level two tiles. Written in json as `{"model": "linear"}` or
+// `{"model": "curve", "points": [{"pixels": 921600, "factor": 1.0}, ...]}`.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(tag = "model", rename_all = "lowercase")]
+pub enum SocScaling {
+    // the same power for every tile size
+    #[default]
+    Constant,
+    // proportional to the pixel count
+    Linear,
+    // interpolated linearly between measured points, the end points hold outside of them
+    Curve { points: Vec<ScalePoint> },
+    // the factor of the largest point not above the pixel count, or of the first point
+    Table { points: Vec<ScalePoint> },
+}
+
+impl SocScaling {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SimulatorError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+        let mut soc_scaling: SocScaling = serde_json::from_reader(file).map_err(|e| SimulatorError::json(&name, e))?;
+        soc_scaling.sort();
+        Ok(soc_scaling)
+    }
+
+    // `constant`, `linear` or the path of a json file, as given on the command line
+    pub fn from_arg(arg: &str) -> Result<Self, SimulatorError> {
+        match arg {
+            "constant" => Ok(SocScaling::Constant),
+            "linear" => Ok(SocScaling::Linear),
+            path => SocScaling::load(path),
+        }
+    }
+
+    // the points of a curve or table, sorted by pixel count
+    pub(crate) fn points(&self) -> Option<&[ScalePoint]> {
+        match *self {
+            SocScaling::Curve { ref points } | SocScaling::Table { ref points } => Some(points),
+            SocScaling::Constant | SocScaling::Linear => None,
+        }
+    }
+
+    pub(crate) fn sort(&mut self) {
+        if let SocScaling::Curve { ref mut points } | SocScaling::Table { ref mut points } = *self {
+            points.sort_by(|a, b| a.pixels.total_cmp(&b.pixels));
+        }
+    }
+
+    pub fn factor(&self, size: Size) -> f64 {
+        let pixels = size.width as f64 * size.height as f64;
+        match *self {
+            SocScaling::Constant => 1.0,
+            SocScaling::Linear => {
+                pixels / (constants::SOC_REFERENCE_WIDTH as f64 * constants::SOC_REFERENCE_HEIGHT as f64)
+            }
+            SocScaling::Curve { ref points } => {
+                match points.iter().position(|p| p.pixels > pixels) {
+                    Some(0) => points[0].factor,
+                    Some(i) => {
+                        let (low, high) = (points[i - 1], points[i]);
+                        low.factor + (high.factor - low.factor) * (pixels - low.pixels) / (high.pixels - low.pixels)
+                    }
+                    None => points[points.len() - 1].factor,
+                }
+            }
+            SocScaling::Table { ref points } => {
+                points.iter().rev().find(|p| p.pixels <= pixels).unwrap_or(&points[0]).factor
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use config::ConfigError;
+use error::SimulatorError;
+
+use std::fs::File;
+use std::path::Path;
+
+extern crate serde_json;
+
+// Level fetched ahead with a whole segment.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+pub enum PrefetchLevel {
+    #[serde(rename = "l2")]
+    LevelTwo,
+    #[serde(rename = "l3")]
+    LevelThree,
+}
+
+// Fetches `level` for every frame of a segment, along with the segment, when the prediction is
+// less than `below` confident in it instead of waiting for a miss. The confidence of a segment is
+// the level one cover ratio of the best object path at the frame before it, scaled by the
+// confidence in percent of the user viewport at that frame; the first segment is never
+// prefetched. Level two needs a level two larger than level one, and prefetching a ladder or a
+// network to size the bytes. Written in json like `{"level": "l3", "below": 0.9}`.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub struct Prefetch {
+    pub level: PrefetchLevel,
+    pub below: f64,
+}
+
+impl Prefetch {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SimulatorError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        let file = File::open(path).map_err(|e| SimulatorError::io(&name, e))?;
+        let prefetch: Prefetch = serde_json::from_reader(file).map_err(|e| SimulatorError::json(&name, e))?;
+        prefetch.validate()?;
+        Ok(prefetch)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(0.0..=1.0).contains(&self.below) {
+            return Err(ConfigError::InvalidPrefetch(format!("confidence {} is not within [0, 1]", self.below)));
+        }
+        Ok(())
+    }
+
+    // whether a segment predicted with `confidence` is prefetched
+    pub fn prefetches(&self, confidence: f64) -> bool {
+        confidence < self.below
+    }
+}
+
+// Segments prefetched over a run and those of them no frame needed the prefetched level of, with
+// the bytes prefetched for nothing and their share of the wifi power by bytes.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize)]
+pub struct PrefetchReport {
+    pub segments: f64,
+    pub wasted_segments: f64,
+    pub wasted_bytes: f64,
+    pub wasted_pc: f64,
+}
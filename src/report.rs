@@ -0,0 +1,341 @@
+use config::{OptimizeVersion, SimulatorConfig};
+use ds::Size;
+use output::Row;
+use network::NetworkReport;
+use power::{Energy, SocComponents};
+use prefetch::PrefetchReport;
+use timeline::Timeline;
+
+use std::f64;
+
+// parameters a report was simulated with, everything of `SimulatorConfig` but the power constants
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub struct SimulationParameters {
+    pub threshold: f64,
+    pub segment: usize,
+    pub level_one: Size,
+    pub level_two: Size,
+    pub opt: OptimizeVersion,
+}
+
+impl<'a> From<&'a SimulatorConfig> for SimulationParameters {
+    fn from(config: &'a SimulatorConfig) -> Self {
+        SimulationParameters {
+            threshold: config.threshold,
+            segment: config.segment,
+            level_one: config.level_one,
+            level_two: config.level_two,
+            opt: config.opt,
+        }
+    }
+}
+
+// Outcome of simulating one user watching one video. Hit counts and ratios are per cache level,
+// level one first; `wifi_pc` and `soc_pc` are the average power of the run in the unit of the
+// power constants.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulationReport {
+    // name of the video in the power constants, e.g. `Elephant`
+    pub video: String,
+    // name of the user viewport file without extension, e.g. `uid-a413ecca-...`
+    pub user: String,
+    pub parameters: SimulationParameters,
+    pub frame_count: usize,
+    pub hit_counts: [usize; 3],
+    pub hit_ratios: [f64; 3],
+    pub segment_count: usize,
+    pub segment_resend_count: usize,
+    pub wifi_pc: f64,
+    pub soc_pc: f64,
+    // `soc_pc` split into decode, render and reprojection
+    pub soc_components: SocComponents,
+    // power of the display panel, 0 without a display model
+    pub display_pc: f64,
+    // transfers over the network model, none without one
+    pub network: Option<NetworkReport>,
+    // bytes sent of every level, level one first, and of streaming the full 360 video instead;
+    // none without a bitrate ladder or a network
+    pub level_bytes: Option<[f64; 3]>,
+    pub full_bytes: Option<f64>,
+    // mean height of the level three quality over the segments falling back, 0 without any, and
+    // how often it changed; none without a policy
+    pub level_three_height: Option<f64>,
+    pub level_three_switches: Option<usize>,
+    // segments prefetched and what they wasted, none without prefetching
+    pub prefetch: Option<PrefetchReport>,
+    // the powers in watts, and in joules over the frames at the frame rate of the video
+    pub energy: Energy,
+    // power of every frame, only kept when asked for as it is as long as the video
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeline: Option<Timeline>,
+}
+
+impl SimulationReport {
+    // share of segments served without fetching the full 360 frame
+    pub fn no_resend_segment_rate(&self) -> f64 {
+        1.0 - self.segment_resend_count as f64 / self.segment_count as f64
+    }
+}
+
+impl Row for SimulationReport {
+    fn columns() -> &'static [&'static str] {
+        &["video", "user", "threshold", "segment", "l1", "l2", "opt", "frames", "l1_hits", "l2_hits", "l3_hits",
+          "l1_hit", "l2_hit", "l3_hit", "segments", "resent_segments", "wifi_pc", "soc_pc",
+          "soc_decode_pc", "soc_render_pc", "soc_reproject_pc", "display_pc",
+          "duration_s", "wifi_w", "soc_w", "display_w", "wifi_j", "soc_j", "display_j",
+          "bytes", "active_s", "tail_s", "idle_s", "late", "late_l3", "late_s",
+          "startup_s", "stalls", "stall_s", "l1_bytes", "l2_bytes", "l3_bytes", "full_bytes", "savings",
+          "l3_height", "l3_switches", "prefetched", "wasted", "wasted_bytes", "wasted_pc"]
+    }
+
+    fn values(&self) -> Vec<String> {
+        let p = &self.parameters;
+        let mut values = vec![self.video.clone(), self.user.clone(), p.threshold.to_string(), p.segment.to_string(),
+             p.level_one.to_string(), p.level_two.to_string(), p.opt.to_string(), self.frame_count.to_string(),
+             self.hit_counts[0].to_string(), self.hit_counts[1].to_string(), self.hit_counts[2].to_string(),
+             self.hit_ratios[0].to_string(), self.hit_ratios[1].to_string(), self.hit_ratios[2].to_string(),
+             self.segment_count.to_string(), self.segment_resend_count.to_string(),
+             self.wifi_pc.to_string(), self.soc_pc.to_string(), self.soc_components.decode.to_string(),
+             self.soc_components.render.to_string(), self.soc_components.reproject.to_string(),
+             self.display_pc.to_string()];
+        values.extend(energy_values(&self.energy));
+        values.extend(network_values(self.network.as_ref()));
+        values.extend(bytes_values(self.level_bytes, self.full_bytes));
+        values.extend(vec![optional(self.level_three_height), optional(self.level_three_switches)]);
+        values.extend(prefetch_values(self.prefetch.as_ref()));
+        values
+    }
+}
+
+// a value of a model that was not configured is left empty, unlike a measured 0
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or(String::new(), |v| v.to_string())
+}
+
+// the bytes of every level, of the full 360 video and the share of them saved
+fn bytes_values(level_bytes: Option<[f64; 3]>, full_bytes: Option<f64>) -> Vec<String> {
+    match (level_bytes, full_bytes) {
+        (Some(level_bytes), Some(full_bytes)) => {
+            let savings = if full_bytes > 0.0 { 1.0 - level_bytes.iter().sum::<f64>() / full_bytes } else { 0.0 };
+            vec![level_bytes[0].to_string(), level_bytes[1].to_string(), level_bytes[2].to_string(),
+                 full_bytes.to_string(), savings.to_string()]
+        }
+        _ => vec![String::new(); 5],
+    }
+}
+
+fn prefetch_values(prefetch: Option<&PrefetchReport>) -> Vec<String> {
+    match prefetch {
+        Some(prefetch) => vec![prefetch.segments.to_string(), prefetch.wasted_segments.to_string(),
+                               prefetch.wasted_bytes.to_string(), prefetch.wasted_pc.to_string()],
+        None => vec![String::new(); 4],
+    }
+}
+
+fn network_values(network: Option<&NetworkReport>) -> Vec<String> {
+    match network {
+        Some(network) => vec![network.bytes.to_string(), network.active_time.to_string(), network.tail_time.to_string(),
+                              network.idle_time.to_string(), network.late_transfers.to_string(),
+                              network.late_fallbacks.to_string(), network.late_time.to_string(),
+                              network.startup_latency.to_string(), network.stalls.to_string(),
+                              network.stall_time.to_string()],
+        None => vec![String::new(); 10],
+    }
+}
+
+fn energy_values(energy: &Energy) -> Vec<String> {
+    vec![energy.duration.to_string(), energy.wifi_watts.to_string(), energy.soc_watts.to_string(),
+         energy.display_watts.to_string(), energy.wifi_joules.to_string(), energy.soc_joules.to_string(),
+         energy.display_joules.to_string()]
+}
+
+// Spread of one metric over the users of a summary. The variance is the population variance,
+// percentiles interpolate linearly between the closest users; all are NaN without users.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    pub mean: f64,
+    pub variance: f64,
+    pub min: f64,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl Stats {
+    pub fn new(values: &[f64]) -> Self {
+        let count = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / count;
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        Stats {
+            mean,
+            variance,
+            min: Self::percentile(&sorted, 0.0),
+            p10: Self::percentile(&sorted, 10.0),
+            p50: Self::percentile(&sorted, 50.0),
+            p90: Self::percentile(&sorted, 90.0),
+            max: Self::percentile(&sorted, 100.0),
+        }
+    }
+
+    fn percentile(sorted: &[f64], p: f64) -> f64 {
+        if sorted.is_empty() {
+            return f64::NAN;
+        }
+        let rank = p / 100.0 * (sorted.len() - 1) as f64;
+        let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+        sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+    }
+
+    // everything but the mean, in the order of the spread columns of `SummaryReport`
+    fn spread(&self) -> [f64; 6] {
+        [self.variance, self.min, self.p10, self.p50, self.p90, self.max]
+    }
+}
+
+// Summary over the reports of every user of a video simulated with the same parameters: the
+// mean of each metric, as in the plain output, and its spread over the users.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SummaryReport {
+    pub video: String,
+    // number of users summarized, users that failed to simulate are left out
+    pub users: usize,
+    pub parameters: SimulationParameters,
+    pub wifi_pc: Stats,
+    pub soc_pc: Stats,
+    pub hit_ratios: [Stats; 3],
+    pub segment_hit: Stats,
+    // mean split of the soc power
+    pub soc_components: SocComponents,
+    // mean duration, power and energy
+    pub energy: Energy,
+    // mean transfers
+    pub network: Option<NetworkReport>,
+    // mean bytes of every level and of the full 360 video
+    pub level_bytes: Option<[f64; 3]>,
+    pub full_bytes: Option<f64>,
+    // mean level three height and switches
+    pub level_three_height: Option<f64>,
+    pub level_three_switches: Option<f64>,
+    // mean segments prefetched and wasted
+    pub prefetch: Option<PrefetchReport>,
+}
+
+impl SummaryReport {
+    pub fn new(parameters: SimulationParameters, reports: &[&SimulationReport]) -> Self {
+        let stats = |metric: &dyn Fn(&SimulationReport) -> f64| {
+            Stats::new(&reports.iter().map(|r| metric(r)).collect::<Vec<f64>>())
+        };
+        SummaryReport {
+            video: reports.first().map(|r| r.video.clone()).unwrap_or_default(),
+            users: reports.len(),
+            parameters,
+            wifi_pc: stats(&|r| r.wifi_pc),
+            soc_pc: stats(&|r| r.soc_pc),
+            hit_ratios: [stats(&|r| r.hit_ratios[0]), stats(&|r| r.hit_ratios[1]), stats(&|r| r.hit_ratios[2])],
+            segment_hit: stats(&|r| r.no_resend_segment_rate()),
+            soc_components: SocComponents {
+                decode: stats(&|r| r.soc_components.decode).mean,
+                render: stats(&|r| r.soc_components.render).mean,
+                reproject: stats(&|r| r.soc_components.reproject).mean,
+            },
+            energy: Energy {
+                duration: stats(&|r| r.energy.duration).mean,
+                wifi_watts: stats(&|r| r.energy.wifi_watts).mean,
+                soc_watts: stats(&|r| r.energy.soc_watts).mean,
+                display_watts: stats(&|r| r.energy.display_watts).mean,
+                wifi_joules: stats(&|r| r.energy.wifi_joules).mean,
+                soc_joules: stats(&|r| r.energy.soc_joules).mean,
+                display_joules: stats(&|r| r.energy.display_joules).mean,
+            },
+            network: mean_network(reports),
+            level_bytes: mean_level_bytes(reports),
+            full_bytes: present_mean(reports, &|r| r.full_bytes),
+            level_three_height: present_mean(reports, &|r| r.level_three_height),
+            level_three_switches: present_mean(reports, &|r| r.level_three_switches.map(|s| s as f64)),
+            prefetch: mean_prefetch(reports),
+        }
+    }
+
+    fn metrics(&self) -> [&Stats; 6] {
+        [&self.wifi_pc, &self.soc_pc, &self.hit_ratios[0], &self.hit_ratios[1], &self.hit_ratios[2], &self.segment_hit]
+    }
+}
+
+// mean of a metric over the reports, none without reports or when one lacks it
+fn present_mean(reports: &[&SimulationReport], metric: &dyn Fn(&SimulationReport) -> Option<f64>) -> Option<f64> {
+    let values: Option<Vec<f64>> = reports.iter().map(|r| metric(r)).collect();
+    values.filter(|v| !v.is_empty()).map(|v| Stats::new(&v).mean)
+}
+
+fn mean_network(reports: &[&SimulationReport]) -> Option<NetworkReport> {
+    let mean = |metric: &dyn Fn(&NetworkReport) -> f64| present_mean(reports, &|r| r.network.as_ref().map(metric));
+    Some(NetworkReport {
+        bytes: mean(&|n| n.bytes)?,
+        active_time: mean(&|n| n.active_time)?,
+        tail_time: mean(&|n| n.tail_time)?,
+        idle_time: mean(&|n| n.idle_time)?,
+        late_transfers: mean(&|n| n.late_transfers)?,
+        late_fallbacks: mean(&|n| n.late_fallbacks)?,
+        late_time: mean(&|n| n.late_time)?,
+        startup_latency: mean(&|n| n.startup_latency)?,
+        stalls: mean(&|n| n.stalls)?,
+        stall_time: mean(&|n| n.stall_time)?,
+    })
+}
+
+fn mean_level_bytes(reports: &[&SimulationReport]) -> Option<[f64; 3]> {
+    let mean = |level: usize| present_mean(reports, &|r| r.level_bytes.map(|b| b[level]));
+    Some([mean(0)?, mean(1)?, mean(2)?])
+}
+
+fn mean_prefetch(reports: &[&SimulationReport]) -> Option<PrefetchReport> {
+    let mean = |metric: &dyn Fn(&PrefetchReport) -> f64| present_mean(reports, &|r| r.prefetch.as_ref().map(metric));
+    Some(PrefetchReport {
+        segments: mean(&|p| p.segments)?,
+        wasted_segments: mean(&|p| p.wasted_segments)?,
+        wasted_bytes: mean(&|p| p.wasted_bytes)?,
+        wasted_pc: mean(&|p| p.wasted_pc)?,
+    })
+}
+
+impl Row for SummaryReport {
+    // the means first, with the columns of the average of earlier versions, then the spread of
+    // every metric, e.g. `wifi_pc_var` or `l1_hit_p10`, then the split of the soc power, the energy,
+    // the transfers, the bytes, the level three quality and the prefetching
+    fn columns() -> &'static [&'static str] {
+        &["video", "users", "threshold", "segment", "l1", "l2", "opt",
+          "wifi_pc", "soc_pc", "l1_hit", "l2_hit", "l3_hit", "segment_hit",
+          "wifi_pc_var", "wifi_pc_min", "wifi_pc_p10", "wifi_pc_p50", "wifi_pc_p90", "wifi_pc_max",
+          "soc_pc_var", "soc_pc_min", "soc_pc_p10", "soc_pc_p50", "soc_pc_p90", "soc_pc_max",
+          "l1_hit_var", "l1_hit_min", "l1_hit_p10", "l1_hit_p50", "l1_hit_p90", "l1_hit_max",
+          "l2_hit_var", "l2_hit_min", "l2_hit_p10", "l2_hit_p50", "l2_hit_p90", "l2_hit_max",
+          "l3_hit_var", "l3_hit_min", "l3_hit_p10", "l3_hit_p50", "l3_hit_p90", "l3_hit_max",
+          "segment_hit_var", "segment_hit_min", "segment_hit_p10", "segment_hit_p50", "segment_hit_p90", "segment_hit_max",
+          "soc_decode_pc", "soc_render_pc", "soc_reproject_pc",
+          "duration_s", "wifi_w", "soc_w", "display_w", "wifi_j", "soc_j", "display_j",
+          "bytes", "active_s", "tail_s", "idle_s", "late", "late_l3", "late_s",
+          "startup_s", "stalls", "stall_s", "l1_bytes", "l2_bytes", "l3_bytes", "full_bytes", "savings",
+          "l3_height", "l3_switches", "prefetched", "wasted", "wasted_bytes", "wasted_pc"]
+    }
+
+    fn values(&self) -> Vec<String> {
+        let p = &self.parameters;
+        let mut values = vec![self.video.clone(), self.users.to_string(), p.threshold.to_string(), p.segment.to_string(),
+                              p.level_one.to_string(), p.level_two.to_string(), p.opt.to_string()];
+        values.extend(self.metrics().iter().map(|s| s.mean.to_string()));
+        for stats in self.metrics().iter() {
+            values.extend(stats.spread().iter().map(|v| v.to_string()));
+        }
+        let c = &self.soc_components;
+        values.extend(vec![c.decode.to_string(), c.render.to_string(), c.reproject.to_string()]);
+        values.extend(energy_values(&self.energy));
+        values.extend(network_values(self.network.as_ref()));
+        values.extend(bytes_values(self.level_bytes, self.full_bytes));
+        values.extend(vec![optional(self.level_three_height), optional(self.level_three_switches)]);
+        values.extend(prefetch_values(self.prefetch.as_ref()));
+        values
+    }
+}
@@ -1,16 +1,18 @@
-use ds::{Frame, Viewport};
+use ds::{Size, Viewport};
+use config::{OptimizeVersion, SimulatorConfig};
+use dataset::{UserTrace, VideoTraces};
+use error::SimulatorError;
+use network::{NetworkReport, Playback, SegmentFetch, Transfer};
+use power::{Energy, SocComponents};
+use prefetch::{PrefetchLevel, PrefetchReport};
+use report::{SimulationParameters, SimulationReport};
+use timeline::{FrameEnergy, Timeline};
 use constants;
 
-use std::io::prelude::*;
-use std::io::BufReader;
-use std::fs::File;
-use std::error::Error;
-use std::path::Path;
 use std::f64;
+use std::path::Path;
 
-extern crate serde;
-extern crate serde_json;
-
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Copy, Clone)]
 enum CacheLevel {
     LevelOne,
@@ -18,6 +20,7 @@ enum CacheLevel {
     LevelThree,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Copy, Clone)]
 struct Hit {
     index: usize,
@@ -28,173 +31,78 @@ struct Hit {
     height: usize,
 }
 
-#[derive(Clone, Deserialize, Debug)]
-pub struct PowerConstants {
-    name: String,
-    value: f64,
+// One simulated segment: its fetch, the bytes of level one and two sent and of the full 360 video
+// at its best quality, and its frames with those of them that did not need the level it prefetched.
+struct SegmentBytes {
+    index: usize,
+    fetch: SegmentFetch,
+    sent: [f64; 3],
+    frames: usize,
+    wasted_frames: usize,
 }
 
-pub struct Simulator {
-    user_file: String,
-    dump_file: String,
-    cluster_json: String,
-    threshold: f64,
-    segment: usize,
+pub struct Simulator<'a> {
+    video: &'a VideoTraces,
+    user: &'a UserTrace,
+    config: SimulatorConfig,
     segment_count: usize,
-    fov_width: usize,
-    fov_height: usize,
-    path_list: Vec<Vec<Viewport>>,
-    user_fov_list: Vec<Viewport>,
-    level_two_width: usize,
-    level_two_height: usize,
     hit_list_for_soc: Vec<Hit>,
     segment_resend_counter: usize,
-    power_constant_360: Vec<PowerConstants>,
-    power_constant_not_360: Vec<PowerConstants>,
-    opt_flag: bool,
+    // index of every resent segment, in order
+    resent_segments: Vec<usize>,
     wifi_pc: f64,
     soc_pc: f64,
+    soc_components: SocComponents,
+    display_pc: f64,
+    network: NetworkReport,
+    // bytes sent of every level, and of streaming the full 360 video instead
+    level_bytes: [f64; 3],
+    full_bytes: f64,
+    // mean height of the level three quality over the segments falling back, and its changes
+    level_three_height: f64,
+    level_three_switches: usize,
+    // quality chosen for level three by segment index, with a policy
+    level_three_qualities: Vec<(usize, usize)>,
+    // average wifi power of every segment by segment index, with a network model or a ladder
+    segment_wifi: Vec<(usize, f64)>,
+    // confidence of the prediction at every frame, and the index of every prefetched segment
+    confidences: Vec<f64>,
+    prefetched_segments: Vec<usize>,
+    prefetch: PrefetchReport,
 }
 
-#[derive(Deserialize, Debug)]
-struct VideoObject {
-    frame_start: usize,
-    frame_end: usize,
-    size: usize,
-    cluster: Vec<usize>,
-}
-
-fn read_json_cluster_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<VideoObject>, Box<Error>> {
-    let file = File::open(path)?;
-
-    // Read the JSON contents of the file as an instance of `Vec[VideoObject]`.
-    let u = serde_json::from_reader(file)?;
-
-    // Return the `VideoObject`.
-    Ok(u)
-}
-
-impl Simulator {
-    pub fn new(user_file: &String, dump_file: &String, cluster_json: &String, threshold: f64,
-               segment: usize, fov_width: usize, fov_height: usize, level_two_width: usize,
-               level_two_height: usize, power_constant_1080p_360: Vec<PowerConstants>,
-               power_constant_1080p: Vec<PowerConstants>, opt_flag: bool) -> Self {
-        let mut level_two_height = level_two_height;
-        if level_two_height > constants::FULL_SIZE_HEIGHT_USIZE {
-            level_two_height = constants::FULL_SIZE_HEIGHT_USIZE;
-        }
-        let mut sim = Simulator {
-            user_file: user_file.to_string(),
-            dump_file: dump_file.to_string(),
-            cluster_json: cluster_json.to_string(),
-            threshold,
-            segment,
+impl<'a> Simulator<'a> {
+    pub fn new(video: &'a VideoTraces, user: &'a UserTrace, config: SimulatorConfig) -> Self {
+        Simulator {
+            video,
+            user,
+            config,
             segment_count: 0,
-            fov_width,
-            fov_height,
-            path_list: vec![],
-            user_fov_list: vec![],
-            level_two_width,
-            level_two_height,
             hit_list_for_soc: vec![],
             segment_resend_counter: 0,
-            power_constant_360: power_constant_1080p_360,
-            power_constant_not_360: power_constant_1080p,
-            opt_flag,
+            resent_segments: vec![],
             wifi_pc: 0.0,
             soc_pc: 0.0,
-        };
-        sim.parse_tracing_to_path_list();
-        sim.parse_user_data();
-        sim
-    }
-
-    // update self.path_list
-    fn parse_tracing_to_path_list(&mut self) {
-        let file = File::open(&self.dump_file).unwrap();
-        let buf_reader = BufReader::new(&file);
-        let mut traces: Vec<Viewport> = vec![];
-        let mut frame_id = 0;
-        let mut frame_list: Vec<Frame> = vec![];
-
-        for line in buf_reader.lines() {
-            let line = line.unwrap();
-            let id_vec: Vec<&str> = line.split(" ").collect();
-            frame_id = (&id_vec[0]).parse::<i32>().unwrap();
-            let object_id = (&id_vec[1]).parse::<i32>().unwrap();
-
-            let coord: Vec<&str> = id_vec[2].split(",").collect();
-            let x = (&coord[0]).parse::<i32>().unwrap();
-            let y = (&coord[1]).parse::<i32>().unwrap();
-            let width = (&coord[2]).parse::<usize>().unwrap();
-            let height = (&coord[3]).parse::<usize>().unwrap();
-            let viewport = Viewport::new(100, x, y, width, height);
-
-            if object_id == 0 {
-                if frame_id != 1 {
-                    frame_list.push(Frame::new(frame_id, &traces));
-                }
-                traces.clear();
-            }
-            traces.push(viewport);
+            soc_components: SocComponents::default(),
+            display_pc: 0.0,
+            network: NetworkReport::default(),
+            level_bytes: [0.0; 3],
+            full_bytes: 0.0,
+            level_three_height: 0.0,
+            level_three_switches: 0,
+            level_three_qualities: vec![],
+            segment_wifi: vec![],
+            confidences: vec![],
+            prefetched_segments: vec![],
+            prefetch: PrefetchReport::default(),
         }
-        // viewport in frame_list is not normalized using our fov size yet
-        frame_list.push(Frame::new(frame_id, &traces));
-
-        // integrate cluster_json and trace dump
-        let video_objects = read_json_cluster_from_file(&self.cluster_json).unwrap();
-        for video_object in video_objects {
-            // frame_start and frame_end - 1 is for normalize the start id in trace dump file to 0
-            // so that we have the same start id as we get from user_view_port_result
-            let start = video_object.frame_start - 1;
-            let end = video_object.frame_end - 1;
-            let mut _frame_id = start;
-            let mut path: Vec<Viewport> = vec![];
-
-            // iterate all the frames from dumping data
-            for frame in frame_list[start..end].iter() {
-                for cluster in &video_object.cluster {
-                    if *cluster < frame.traces.len() {
-                        let v = frame.traces[*cluster];
-                        path.push(Viewport::create_new_with_size(&v, self.fov_width, self.fov_height));
-                    }
-                }
-                self.path_list.push(path.clone());
-                path.clear();
-            }
-//            println!("{}: {:?}", start, self.path_list[start]);
-        }
-    }
-
-    // update user_fov_list
-    fn parse_user_data(&mut self) {
-        let file = File::open(&self.user_file).unwrap();
-        let buf_reader = BufReader::new(file);
-
-        for line in buf_reader.lines() {
-            let line = line.unwrap();
-            let line_split: Vec<&str> = line.split(" ").collect();
-//            let key = (&line_split[0]).parse::<usize>().unwrap();
-            let conf = (&line_split[1]).parse::<i32>().unwrap();
-
-            let extract: Vec<&str> = line_split[2].split(",").collect();
-            let x = (&extract[0]).parse::<i32>().unwrap();
-            let y = (&extract[1]).parse::<i32>().unwrap();
-            let width = (&extract[2]).parse::<usize>().unwrap();
-            let height = (&extract[3]).parse::<usize>().unwrap();
-            let u_fov = Viewport::new(conf, x, y, width, height);
-//            // assume user_viewport file has key start from 0 and add one consecutively
-            self.user_fov_list.push(u_fov);
-        }
-//        println!("{:?}", self.user_fov_list);
     }
 
-    fn compare_from_level_one(&self, fov: &Viewport, user_fov: &Viewport, index: usize, path: usize, width: usize, height: usize) -> (Hit, CacheLevel) {
-        let ratio = fov.get_cover_result(user_fov);
-        let hit: Hit;
-        if ratio >= self.threshold {
+    fn compare_from_level_one(&self, fov: &Viewport, user_fov: &Viewport, index: usize, path: usize, width: usize, height: usize) -> Result<(Hit, CacheLevel), SimulatorError> {
+        let ratio = fov.get_cover_result(user_fov)?;
+        if ratio >= self.config.threshold {
 //            println!("L1 hit {} at {}", index, ratio);
-            hit = Hit {
+            let hit = Hit {
                 index,
                 ratio,
                 cache_level: CacheLevel::LevelOne,
@@ -202,50 +110,47 @@ impl Simulator {
                 width,
                 height,
             };
-            (hit, CacheLevel::LevelOne)
+            Ok((hit, CacheLevel::LevelOne))
         } else {
             // predict if level-two is actually miss before downloading segment from cloud server
             if self.is_hierarchical() {
-                self.compare_from_level_two(&fov, &user_fov, index, path)
+                self.compare_from_level_two(fov, user_fov, index, path)
             } else {
-                self.compare_from_level_three(index, path)
+                Ok(self.compare_from_level_three(index, path))
             }
         }
     }
 
-    fn compare_from_level_two(&self, fov: &Viewport, user_fov: &Viewport, index: usize, path: usize) -> (Hit, CacheLevel) {
-        let level_one_ratio = fov.get_cover_result(user_fov);
-        let hit: Hit;
-        let level_two_viewport = Viewport::create_new_with_size(&fov, self.level_two_width, self.level_two_height);
-        let level_two_ratio = level_two_viewport.get_cover_result(user_fov);
-        if level_two_ratio >= self.threshold {
+    fn compare_from_level_two(&self, fov: &Viewport, user_fov: &Viewport, index: usize, path: usize) -> Result<(Hit, CacheLevel), SimulatorError> {
+        let level_one_ratio = fov.get_cover_result(user_fov)?;
+        let level_two = self.config.level_two;
+        let level_two_viewport = Viewport::create_new_with_size(fov, level_two.width, level_two.height);
+        let level_two_ratio = level_two_viewport.get_cover_result(user_fov)?;
+        if level_two_ratio >= self.config.threshold {
 //            println!("L2 hit {} at {}", index, level_two_ratio);
-            hit = Hit {
+            let hit = Hit {
                 index,
                 ratio: level_two_ratio,
                 cache_level: CacheLevel::LevelTwo,
                 path,
-                width: self.level_two_width,
-                height: self.level_two_height,
+                width: level_two.width,
+                height: level_two.height,
             };
-            (hit, CacheLevel::LevelTwo)
+            Ok((hit, CacheLevel::LevelTwo))
         } else {
 //            println!("L2 miss {} at {}", index, level_two_ratio);
             if level_two_ratio < level_one_ratio {
-                println!("index: {}, l1 ratio: {}, l2 ratio: {}", index, level_one_ratio, level_two_ratio);
-                println!("l1 {:?}", fov);
-                println!("l2 {:?}", level_two_viewport);
-                println!("user {:?}", user_fov);
-                assert!(false);
+                return Err(SimulatorError::GeometryInvariant(format!(
+                    "level two covers less than level one at frame {} (l1 ratio {}, l2 ratio {}, l1 {:?}, l2 {:?}, user {:?})",
+                    index, level_one_ratio, level_two_ratio, fov, level_two_viewport, user_fov)));
             }
-            self.compare_from_level_three(index, path)
+            Ok(self.compare_from_level_three(index, path))
         }
     }
 
     fn compare_from_level_three(&self, index: usize, path: usize) -> (Hit, CacheLevel) {
 //        println!("L3 hit {} at {}", index, 1);
-        let hit: Hit;
-        hit = Hit {
+        let hit = Hit {
             index,
             ratio: 1.0,
             cache_level: CacheLevel::LevelThree,
@@ -257,15 +162,15 @@ impl Simulator {
     }
 
     fn is_hierarchical(&self) -> bool {
-        if self.fov_width == self.level_two_width && self.fov_height == self.level_two_height {
-            false
-        } else {
-            true
-        }
+        self.config.level_one != self.config.level_two
     }
 
     // simulate with hierarchical or non-hierarchical with segment and threshold implicitly
-    pub fn simulate(&mut self) {
+    pub fn simulate(&mut self) -> Result<SimulationReport, SimulatorError> {
+        // no frame would leave every ratio 0 / 0
+        if self.user.fov_list().is_empty() || self.video.frame_count() == 0 {
+            return Err(SimulatorError::EmptyTrace { file: self.user.user_file().to_string() });
+        }
         let mut current_path: Option<usize> = None;
         let mut hit_soc_cache_pair: (Hit, CacheLevel) = (Hit {
             index: 0,
@@ -275,32 +180,37 @@ impl Simulator {
             width: 0,
             height: 0,
         }, CacheLevel::LevelOne);
-        for (k, user_fov) in self.user_fov_list.iter().enumerate() {
+        let user = self.user;
+        for (k, user_fov) in user.fov_list().iter().enumerate() {
             // Variables for soc calculation
             let mut max_ratio: f64 = f64::NEG_INFINITY;
             let mut max_ratio_path: Option<usize> = None;
-            let mut temp_viewport: Option<&Viewport> = None;
-            let width = self.fov_width;
-            let height = self.fov_height;
+            let mut temp_viewport: Option<Viewport> = None;
+            let width = self.config.level_one.width;
+            let height = self.config.level_one.height;
 
             // Variables for wifi calculation
 //            let resend_flag = false;
 
-            if self.path_list.len() > k {
-                for (path, path_viewport) in self.path_list[k].iter().enumerate() {
-                    let current_ratio = path_viewport.get_cover_result(user_fov);
+            if self.video.frame_count() > k {
+                for (path, object_viewport) in self.video.paths(k).iter().enumerate() {
+                    let path_viewport = Viewport::create_new_with_size(object_viewport, width, height);
+                    let current_ratio = path_viewport.get_cover_result(user_fov)?;
                     if max_ratio < current_ratio {
                         max_ratio = current_ratio;
                         max_ratio_path = Some(path);
                         temp_viewport = Some(path_viewport);
                     }
                 }
+                if max_ratio_path.is_none() {
+                    return Err(SimulatorError::GeometryInvariant(format!("no object path at frame {}", k)));
+                }
 
                 // Compute SOC
-                if k % self.segment == 0 {
+                if k % self.config.segment == 0 {
                     // the first frame in the segment
                     current_path = max_ratio_path;
-                    hit_soc_cache_pair = self.compare_from_level_one(&temp_viewport.unwrap(), &user_fov, k, max_ratio_path.unwrap(), width, height);
+                    hit_soc_cache_pair = self.compare_from_level_one(&temp_viewport.unwrap(), user_fov, k, max_ratio_path.unwrap(), width, height)?;
                 } else {
                     // the rest frames except for the first one in the segment
 //                    println!("k: {}, path: {}, ratio: {}", k, max_ratio_path.unwrap(), max_ratio);
@@ -310,14 +220,14 @@ impl Simulator {
                         match hit_soc_cache_pair.1 {
                             CacheLevel::LevelOne => {
                                 if current_path == max_ratio_path {
-                                    hit_soc_cache_pair = self.compare_from_level_one(&temp_viewport.unwrap(), &user_fov, k, max_ratio_path.unwrap(), width, height);
+                                    hit_soc_cache_pair = self.compare_from_level_one(&temp_viewport.unwrap(), user_fov, k, max_ratio_path.unwrap(), width, height)?;
                                 } else {
                                     hit_soc_cache_pair = self.compare_from_level_three(k, max_ratio_path.unwrap());
                                 }
                             }
                             CacheLevel::LevelTwo => {
                                 if current_path == max_ratio_path {
-                                    hit_soc_cache_pair = self.compare_from_level_two(&temp_viewport.unwrap(), &user_fov, k, max_ratio_path.unwrap());
+                                    hit_soc_cache_pair = self.compare_from_level_two(&temp_viewport.unwrap(), user_fov, k, max_ratio_path.unwrap())?;
                                 } else {
                                     hit_soc_cache_pair = self.compare_from_level_three(k, current_path.unwrap());
                                 }
@@ -330,40 +240,255 @@ impl Simulator {
                         match hit_soc_cache_pair.1 {
                             CacheLevel::LevelOne => {
                                 if current_path == max_ratio_path {
-                                    hit_soc_cache_pair = self.compare_from_level_one(&temp_viewport.unwrap(), &user_fov, k, max_ratio_path.unwrap(), width, height);
+                                    hit_soc_cache_pair = self.compare_from_level_one(&temp_viewport.unwrap(), user_fov, k, max_ratio_path.unwrap(), width, height)?;
                                 } else {
                                     hit_soc_cache_pair = self.compare_from_level_three(k, max_ratio_path.unwrap());
                                 }
                             }
-                            CacheLevel::LevelTwo => assert!(false),
+                            CacheLevel::LevelTwo => unreachable!("level two hit without hierarchy"),
                             CacheLevel::LevelThree => hit_soc_cache_pair = self.compare_from_level_three(k, current_path.unwrap()),
                         }
                     }
                 }
-                self.hit_list_for_soc.push(hit_soc_cache_pair.0.clone());
+                self.hit_list_for_soc.push(hit_soc_cache_pair.0);
+                self.confidences.push(max_ratio * user_fov.get_conf() as f64 / 100.0);
             }
 
             // Count resend segments for network power calculation
-            if k % self.segment == self.segment - 1 {
-                match hit_soc_cache_pair.1 {
-                    CacheLevel::LevelThree =>  self.segment_resend_counter += 1,
-                    _ => ()
+            if k % self.config.segment == self.config.segment - 1 {
+                if let CacheLevel::LevelThree = hit_soc_cache_pair.1 {
+                    self.segment_resend_counter += 1;
+                    self.resent_segments.push(k / self.config.segment);
                 }
             }
         }
 
-        self.segment_count = (self.hit_list_for_soc.len() as f64 / self.segment as f64).ceil() as usize;
+        self.segment_count = (self.hit_list_for_soc.len() as f64 / self.config.segment as f64).ceil() as usize;
+
+        // a segment is prefetched on the confidence of the frame before it
+        if let Some(prefetch) = self.config.prefetch {
+            let segment = self.config.segment;
+            let prefetched = (1..self.hit_list_for_soc.len())
+                .filter(|&i| self.hit_list_for_soc[i].index.is_multiple_of(segment) && prefetch.prefetches(self.confidences[i - 1]))
+                .map(|i| self.hit_list_for_soc[i].index / segment)
+                .collect();
+            self.prefetched_segments = prefetched;
+        }
 
 //        assert_eq!(self.hit_list.len(), self.user_fov_list.len());
 //        println!("total segment: {}, segment_resend_counter: {}", self.user_fov_list.len() / 20, self.segment_resend_counter);
 
         // fill wifi_pc and soc_pc
-        self.power_consumption();
+        self.power_consumption()?;
+        Ok(self.report())
+    }
+
+    pub fn report(&self) -> SimulationReport {
+        let user_file = self.user.user_file();
+        SimulationReport {
+            video: self.video.name().to_string(),
+            user: Path::new(user_file).file_stem().map_or(user_file.to_string(), |s| s.to_string_lossy().into_owned()),
+            parameters: SimulationParameters::from(&self.config),
+            frame_count: self.hit_list_for_soc.len(),
+            hit_counts: *self.get_hit_counts(),
+            hit_ratios: *self.get_hit_ratios(),
+            segment_count: self.segment_count,
+            segment_resend_count: self.segment_resend_counter,
+            wifi_pc: self.wifi_pc,
+            soc_pc: self.soc_pc,
+            soc_components: self.soc_components,
+            display_pc: self.display_pc,
+            network: self.get_network(),
+            level_bytes: self.get_level_bytes(),
+            full_bytes: self.get_level_bytes().map(|_| self.full_bytes),
+            level_three_height: self.config.abr.as_ref().map(|_| self.level_three_height),
+            level_three_switches: self.config.abr.as_ref().map(|_| self.level_three_switches),
+            prefetch: self.get_prefetch(),
+            energy: Energy::new(self.wifi_pc, self.soc_pc, self.display_pc, self.hit_list_for_soc.len(),
+                                self.video.frame_rate()),
+            timeline: None,
+        }
+    }
+
+    // Power of every simulated frame from the cache level it was served from, and of every
+    // segment. Frames are charged like `power_consumption` charges the hit ratios.
+    pub fn timeline(&self) -> Result<Timeline, SimulatorError> {
+        let video_name = self.video.name();
+        let levels = [CacheLevel::LevelOne, CacheLevel::LevelTwo, CacheLevel::LevelThree];
+        let mut wifi = [0.0; 3];
+        let mut soc = [0.0; 3];
+        for (i, &level) in levels.iter().enumerate() {
+            wifi[i] = self.get_wifi_power_constant(video_name, level)?;
+            soc[i] = self.get_soc_power_constant(video_name, level)?;
+        }
+
+        let mut frames = vec![];
+        let mut previous_path = None;
+        for hit in &self.hit_list_for_soc {
+            let segment_index = hit.index / self.config.segment;
+            let resent = self.resent_segments.binary_search(&segment_index).is_ok();
+            let level = match hit.cache_level {
+                CacheLevel::LevelOne => 0,
+                CacheLevel::LevelTwo => 1,
+                CacheLevel::LevelThree => 2,
+            };
+            let wifi_pc = if self.config.network.is_some() || self.config.ladder.is_some() {
+                // the wifi power of the segment the frame came with
+                self.segment_wifi.binary_search_by_key(&segment_index, |s| s.0).map_or(0.0, |i| self.segment_wifi[i].1)
+            } else {
+                self.sent_levels(hit, resent).iter().zip(&wifi)
+                    .filter(|&(&sent, _)| sent)
+                    .map(|(_, wifi)| wifi)
+                    .sum()
+            };
+            let user_fov = self.user.fov_list()[hit.index];
+            frames.push(FrameEnergy {
+                frame: hit.index,
+                segment_index,
+                level: level + 1,
+                ratio: hit.ratio,
+                path: hit.path,
+                path_changed: previous_path.is_some_and(|path| path != hit.path),
+                user_x: user_fov.x,
+                user_y: user_fov.y,
+                resent,
+                wifi_pc,
+                soc_pc: if level == 2 { self.get_level_three_components(video_name, segment_index)?.total() } else { soc[level] },
+                display_pc: self.get_display_power_constant(hit.index),
+            });
+            previous_path = Some(hit.path);
+        }
+        Ok(Timeline::new(frames))
+    }
+
+    // cache levels sent for a frame, level one always and then what the frame was served from
+    fn transferred_levels(&self, hit: &Hit, resent: bool) -> [bool; 3] {
+        if !self.is_hierarchical() {
+            // without level two the whole segment is sent again on a miss
+            return [true, false, resent];
+        }
+        match hit.cache_level {
+            CacheLevel::LevelOne => [true, false, false],
+            CacheLevel::LevelTwo => [true, true, false],
+            // the sensor prediction skips level two when it misses
+            CacheLevel::LevelThree if self.config.opt == OptimizeVersion::O1 => [true, false, true],
+            CacheLevel::LevelThree => [true, true, true],
+        }
+    }
+
+    // level the segment at `segment_index` prefetches, if it is prefetched
+    fn prefetched_level(&self, segment_index: usize) -> Option<usize> {
+        let prefetch = self.config.prefetch?;
+        self.prefetched_segments.binary_search(&segment_index).ok()?;
+        Some(match prefetch.level {
+            PrefetchLevel::LevelTwo => 1,
+            PrefetchLevel::LevelThree => 2,
+        })
+    }
+
+    // cache levels sent for a frame, those it needs and the one its segment prefetches
+    fn sent_levels(&self, hit: &Hit, resent: bool) -> [bool; 3] {
+        let mut levels = self.transferred_levels(hit, resent);
+        if let Some(level) = self.prefetched_level(hit.index / self.config.segment) {
+            levels[level] = true;
+        }
+        levels
+    }
+
+    // sizes of level three, best first: the qualities of the policy, or else the full frame
+    fn level_three_sizes(&self) -> Vec<Size> {
+        match self.config.abr {
+            Some(ref abr) => abr.qualities.clone(),
+            None => vec![Size::new(constants::FULL_SIZE_WIDTH_USIZE, constants::FULL_SIZE_HEIGHT_USIZE)],
+        }
+    }
+
+    // Bytes of one frame of level one, level two and then level three at every quality, in the
+    // segment at `segment_index`: from the bitrate ladder of the video, or else the bitrates of the
+    // network, none without either.
+    fn frame_bytes(&self, segment_index: usize) -> Result<Vec<f64>, SimulatorError> {
+        let frame_rate = self.video.frame_rate();
+        let video = self.video.name();
+        let mut sizes = vec![self.config.level_one, self.config.level_two];
+        sizes.extend(self.level_three_sizes());
+        if let Some(ladder) = self.config.ladder.as_ref().and_then(|ladder| ladder.video(video)) {
+            return sizes.iter()
+                .map(|&size| ladder.frame_bytes(size, segment_index, self.config.segment, frame_rate)
+                    .ok_or_else(|| SimulatorError::UnknownBitrate { video: video.to_string(), size }))
+                .collect();
+        }
+        if let Some(ref network) = self.config.network {
+            // level one and two scale the 1920x1080 bitrate by their area, level three the panorama one
+            let bitrate = network.bitrate(video);
+            let area = |size: Size| size.width as f64 * size.height as f64;
+            let full_area = area(Size::new(constants::FULL_SIZE_WIDTH_USIZE, constants::FULL_SIZE_HEIGHT_USIZE));
+            return Ok(sizes.iter().enumerate().map(|(level, &size)| {
+                let mbps = if level < 2 {
+                    bitrate.normal * area(size) / 1920.0 / 1080.0
+                } else {
+                    bitrate.panorama * area(size) / full_area
+                };
+                mbps * 1e6 / 8.0 / frame_rate
+            }).collect());
+        }
+        if self.config.ladder.is_some() {
+            return Err(SimulatorError::UnknownBitrate { video: video.to_string(), size: sizes[0] });
+        }
+        Ok(vec![0.0; sizes.len()])
+    }
+
+    // Every segment as simulated. It fetches the bytes of the levels sent for each of its frames,
+    // level three at every quality from the first frame served from it, or ahead when prefetched.
+    fn segment_fetches(&self) -> Result<Vec<SegmentBytes>, SimulatorError> {
+        let frame_rate = self.video.frame_rate();
+        let mut segments: Vec<SegmentBytes> = vec![];
+        let mut frame_bytes = vec![];
+        for hit in &self.hit_list_for_soc {
+            let segment_index = hit.index / self.config.segment;
+            let prefetched = self.prefetched_level(segment_index);
+            if segments.last().is_none_or(|s| s.index != segment_index) {
+                frame_bytes = self.frame_bytes(segment_index)?;
+                let fetch = SegmentFetch {
+                    duration: 0.0,
+                    bytes: 0.0,
+                    fallback_at: 0.0,
+                    fallback_bytes: vec![],
+                    prefetched: prefetched == Some(2),
+                };
+                segments.push(SegmentBytes { index: segment_index, fetch, sent: [0.0; 3], frames: 0, wasted_frames: 0 });
+            }
+            let resent = self.resent_segments.binary_search(&segment_index).is_ok();
+            let levels = self.sent_levels(hit, resent);
+            let segment = segments.last_mut().unwrap();
+            let fetch = &mut segment.fetch;
+            for level in (0..2).filter(|&level| levels[level]) {
+                fetch.bytes += frame_bytes[level];
+                segment.sent[level] += frame_bytes[level];
+            }
+            if levels[2] {
+                if fetch.fallback_bytes.is_empty() {
+                    fetch.fallback_bytes = vec![0.0; frame_bytes.len() - 2];
+                }
+                for (total, bytes) in fetch.fallback_bytes.iter_mut().zip(&frame_bytes[2..]) {
+                    *total += bytes;
+                }
+            }
+            segment.sent[2] += frame_bytes[2];
+            segment.frames += 1;
+            if prefetched.is_some_and(|level| !self.transferred_levels(hit, resent)[level]) {
+                segment.wasted_frames += 1;
+            }
+            if !matches!(hit.cache_level, CacheLevel::LevelThree) {
+                fetch.fallback_at = fetch.duration + 1.0 / frame_rate;
+            }
+            fetch.duration += 1.0 / frame_rate;
+        }
+        Ok(segments)
     }
 
     pub fn get_hit_counts(&self) -> Box<[usize; 3]> {
         let mut count_arr: Box<[usize; 3]> = Box::new([0, 0, 0]);
-        (&self.hit_list_for_soc).iter().for_each(|&x| match x.cache_level {
+        self.hit_list_for_soc.iter().for_each(|&x| match x.cache_level {
             CacheLevel::LevelOne => count_arr[0] += 1,
             CacheLevel::LevelTwo => count_arr[1] += 1,
             CacheLevel::LevelThree => count_arr[2] += 1,
@@ -393,50 +518,85 @@ impl Simulator {
         acc_hit_ratio
     }
 
-    fn get_wifi_power_constant(&self, video_name: &str, size: CacheLevel) -> f64 {
-        let mut wifi_name: String = video_name.to_owned().to_string();
-        wifi_name.push_str("_WIFI");
-        let wifi_power_not_360 = self.power_constant_not_360.iter().find(|&x| x.name == wifi_name).unwrap().value;
-        let wifi_power_360 = self.power_constant_360.iter().find(|&x| x.name == wifi_name).unwrap().value;
-        match size {
+    fn get_wifi_power_constant(&self, video_name: &str, size: CacheLevel) -> Result<f64, SimulatorError> {
+        let wifi_power_not_360 = self.config.power.get_1080p(video_name)?.wifi;
+        let wifi_power_360 = self.config.power.get_360(video_name)?.wifi;
+        // level one and two are normal videos, so scale the 1080p constant by their resolution
+        let scale_1080p = |size: Size| wifi_power_not_360 * (size.width as f64 * size.height as f64 / 1920.0 / 1080.0);
+        Ok(match size {
             CacheLevel::LevelOne => {
-                wifi_power_not_360 * (self.fov_width as f64 * self.fov_height as f64 / 1920.0 / 1080.0)
+                scale_1080p(self.config.level_one)
+            },
+            CacheLevel::LevelTwo => {
+                scale_1080p(self.config.level_two)
             },
             CacheLevel::LevelThree => {
                 wifi_power_360
             },
-            _ => panic!("l2 get_wifi_power_constant")
-        }
+        })
     }
 
-    fn get_soc_power_constant(&self, video_name: &str, size: CacheLevel) -> f64 {
-        let mut soc_name: String = video_name.to_owned().to_string();
-        soc_name.push_str("_SOC");
-
-        // both total and render is for 1280x720
-        let total = self.power_constant_360.iter().find(|&x| x.name == soc_name).unwrap().value;
-        let render = self.power_constant_not_360.iter().find(|&x| x.name == soc_name).unwrap().value;
-        // TODO since we do not know how to reproject yet, we currently skip this part
-        // let reproject = total - render;
+    // SoC power of a frame served from `size`, by component
+    fn get_soc_level_components(&self, video_name: &str, size: CacheLevel) -> Result<SocComponents, SimulatorError> {
+        // both total and render is for 1280x720, render follows the tile size through `soc_scaling`
+        let total = self.config.power.get_360(video_name)?.soc;
+        let power_1080p = self.config.power.get_1080p(video_name)?;
+        let render = power_1080p.soc;
+        // decoding is only known when measured on its own, otherwise it stays part of render
+        let decode = power_1080p.decode.unwrap_or(0.0);
+        let tile = SocComponents {
+            decode,
+            render: render - decode,
+            reproject: 0.0,
+        };
+        let reproject = total - render;
 
-        match size {
+        Ok(match size {
             CacheLevel::LevelOne => {
-                render
+                tile.scale(self.config.soc_scaling.factor(self.config.level_one))
+            }
+            CacheLevel::LevelTwo => {
+                // level two is large enough to need part of the reprojection of the full frame,
+                // taken in proportion of its area
+                let level_two = self.config.level_two;
+                let full_size = constants::FULL_SIZE_WIDTH_USIZE as f64 * constants::FULL_SIZE_HEIGHT_USIZE as f64;
+                let mut components = tile.scale(self.config.soc_scaling.factor(level_two));
+                if self.config.reproject_level_two {
+                    components.reproject = reproject * (level_two.width as f64 * level_two.height as f64 / full_size);
+                }
+                components
             }
             CacheLevel::LevelThree => {
-                total
+                SocComponents { reproject, ..tile }
             }
-            _ => panic!("yet deal with level 2!!!")
-        }
+        })
     }
 
-    pub fn power_consumption(&mut self) {
-        // extract name from user_file
-        // which for example could be: user_viewport_result/Elephant-training-2bpICIClAIg/uid-a413ecca-3822-47b3-92f3-2e2fbe8470c0.txt
-        let video_name: &str = {
-            let temp_name: &str = self.user_file.split("/").collect::<Vec<_>>()[1];
-            temp_name.split("-").collect::<Vec<_>>()[0]
+    // SoC power of a level three frame of the segment at `segment_index` at the quality chosen for
+    // it, from the full frame: rendering follows `soc_scaling` and reprojection the area
+    fn get_level_three_components(&self, video_name: &str, segment_index: usize) -> Result<SocComponents, SimulatorError> {
+        let full = self.get_soc_level_components(video_name, CacheLevel::LevelThree)?;
+        let size = match self.level_three_qualities.binary_search_by_key(&segment_index, |q| q.0) {
+            Ok(i) => self.level_three_sizes()[self.level_three_qualities[i].1],
+            Err(_) => return Ok(full),
         };
+        let full_size = Size::new(constants::FULL_SIZE_WIDTH_USIZE, constants::FULL_SIZE_HEIGHT_USIZE);
+        let area = |size: Size| size.width as f64 * size.height as f64;
+        let scaling = &self.config.soc_scaling;
+        let render = if scaling.factor(full_size) > 0.0 { scaling.factor(size) / scaling.factor(full_size) } else { 1.0 };
+        Ok(SocComponents { reproject: full.reproject * area(size) / area(full_size), ..full.scale(render) })
+    }
+
+    fn get_soc_power_constant(&self, video_name: &str, size: CacheLevel) -> Result<f64, SimulatorError> {
+        match size {
+            // the full 360 frame is measured as a whole
+            CacheLevel::LevelThree => Ok(self.config.power.get_360(video_name)?.soc),
+            _ => Ok(self.get_soc_level_components(video_name, size)?.total()),
+        }
+    }
+
+    pub fn power_consumption(&mut self) -> Result<(), SimulatorError> {
+        let video_name = self.video.name();
 
         // Get power constant value:
         // 1080p with 1280x720 viewport -> only rendering
@@ -447,13 +607,20 @@ impl Simulator {
 
         // Power constant for each level
         let cache_hit_ratios = self.get_hit_ratios();
-        let wifi_level_one_power_constant = self.get_wifi_power_constant(&video_name, CacheLevel::LevelOne);
-//        let wifi_level_two_power_constant = self.get_wifi_power_constant(&video_name, CacheLevel::LevelTwo);
-        let wifi_level_three_power_constant = self.get_wifi_power_constant(&video_name, CacheLevel::LevelThree);
-
-        let soc_level_one_power_constant = self.get_soc_power_constant(&video_name, CacheLevel::LevelOne);
-//        let soc_level_two_power_constant = self.get_soc_power_constant(&video_name, CacheLevel::LevelTwo);
-        let soc_level_three_power_constant = self.get_soc_power_constant(&video_name, CacheLevel::LevelThree);
+        let wifi_level_one_power_constant = self.get_wifi_power_constant(video_name, CacheLevel::LevelOne)?;
+        let wifi_level_two_power_constant = self.get_wifi_power_constant(video_name, CacheLevel::LevelTwo)?;
+        let wifi_level_three_power_constant = self.get_wifi_power_constant(video_name, CacheLevel::LevelThree)?;
+
+        let soc_level_one_power_constant = self.get_soc_power_constant(video_name, CacheLevel::LevelOne)?;
+        let soc_level_two_power_constant = self.get_soc_power_constant(video_name, CacheLevel::LevelTwo)?;
+        let soc_level_three_power_constant = self.get_soc_power_constant(video_name, CacheLevel::LevelThree)?;
+
+        // split of soc_pc, every level weighted by its hit ratio
+        self.soc_components = SocComponents::default();
+        for (i, &level) in [CacheLevel::LevelOne, CacheLevel::LevelTwo, CacheLevel::LevelThree].iter().enumerate() {
+            let components = self.get_soc_level_components(video_name, level)?;
+            self.soc_components = self.soc_components.add(&components.scale(cache_hit_ratios[i]));
+        }
 
 //        println!("DEBUG {} {} {} {} {} {}",
 //                 wifi_level_one_power_constant, wifi_level_two_power_constant, wifi_level_three_power_constant,
@@ -482,61 +649,182 @@ impl Simulator {
         // look at). The computation of level-1 and level-2 in this optimization is the same in this
         // VR system, however, when computing the power consumption that level-2 has missed, we could
         // simply add up the power constants of level-1 + level-3.
-        if self.is_hierarchical() && (!self.opt_flag) {
-            panic!("with l2");
-//            self.wifi_pc = {
-//                let first_level = cache_hit_ratios[0] * wifi_level_one_power_constant;
-//                let second_level = cache_hit_ratios[1] * (wifi_level_one_power_constant + wifi_level_two_power_constant);
-//                let third_level = cache_hit_ratios[2] * (wifi_level_one_power_constant + wifi_level_two_power_constant + wifi_level_three_power_constant);
-//                first_level + second_level + third_level
-//            };
-//            self.soc_pc = {
-//                let first_level = cache_hit_ratios[0] * soc_level_one_power_constant;
-//                let second_level = cache_hit_ratios[1] * soc_level_two_power_constant;
-//                let third_level = cache_hit_ratios[2] * soc_level_three_power_constant;
-//                println!("FFFF {} {} {}", first_level, second_level, third_level);
-//                first_level + second_level + third_level
-//            };
-        } else if self.is_hierarchical() && self.opt_flag {
-            panic!("with l2 + opt");
-//            self.wifi_pc = {
-//                let first_level = cache_hit_ratios[0] * wifi_level_one_power_constant;
-//                let second_level = cache_hit_ratios[1] * (wifi_level_one_power_constant + wifi_level_two_power_constant);
-//                let third_level = cache_hit_ratios[2] * (wifi_level_one_power_constant + wifi_level_three_power_constant);
-//                println!("LL {} {} {}", first_level, second_level, third_level);
-//                first_level + second_level + third_level
-//            };
-//            self.soc_pc = {
-//                let first_level = cache_hit_ratios[0] * soc_level_one_power_constant;
-//                let second_level = cache_hit_ratios[1] * soc_level_two_power_constant;
-//                let third_level = cache_hit_ratios[2] * soc_level_three_power_constant;
-//                println!("LL {} {} {}", first_level, second_level, third_level);
-//                first_level + second_level + third_level
-//            };
+        let opt_flag = self.config.opt == OptimizeVersion::O1;
+        if self.is_hierarchical() && (!opt_flag) {
+            self.wifi_pc = {
+                let first_level = cache_hit_ratios[0] * wifi_level_one_power_constant;
+                let second_level = cache_hit_ratios[1] * (wifi_level_one_power_constant + wifi_level_two_power_constant);
+                let third_level = cache_hit_ratios[2] * (wifi_level_one_power_constant + wifi_level_two_power_constant + wifi_level_three_power_constant);
+                first_level + second_level + third_level
+            };
+        } else if self.is_hierarchical() && opt_flag {
+            self.wifi_pc = {
+                let first_level = cache_hit_ratios[0] * wifi_level_one_power_constant;
+                let second_level = cache_hit_ratios[1] * (wifi_level_one_power_constant + wifi_level_two_power_constant);
+                let third_level = cache_hit_ratios[2] * (wifi_level_one_power_constant + wifi_level_three_power_constant);
+                first_level + second_level + third_level
+            };
         } else {
+            // without a hierarchy no frame is served from level two
+            if cache_hit_ratios[1] != 0.0 {
+                return Err(SimulatorError::GeometryInvariant(format!(
+                    "level two hit ratio {} without a level two larger than level one", cache_hit_ratios[1])));
+            }
             self.wifi_pc = {
                 let no_resend_segment = self.segment_count - self.segment_resend_counter;
                 let no_resend_power = (no_resend_segment as f64 / self.segment_count as f64) * wifi_level_one_power_constant;
                 let resend_power = (self.segment_resend_counter as f64 / self.segment_count as f64) * (wifi_level_one_power_constant + wifi_level_three_power_constant);
-                assert_eq!(cache_hit_ratios[1], 0.0);
 //                println!("{} {}", no_resend_power, resend_power);
                 no_resend_power + resend_power
             };
-            self.soc_pc = {
-                let first_level = cache_hit_ratios[0] * soc_level_one_power_constant;
-                let third_level = cache_hit_ratios[2] * soc_level_three_power_constant;
-                assert_eq!(cache_hit_ratios[1], 0.0);
-                first_level + third_level
-            };
         }
+        // every frame is rendered from the level it hit, whatever was sent for it; without a
+        // hierarchy no frame hits level two
+        self.soc_pc = {
+            let first_level = cache_hit_ratios[0] * soc_level_one_power_constant;
+            let second_level = cache_hit_ratios[1] * soc_level_two_power_constant;
+            let third_level = cache_hit_ratios[2] * soc_level_three_power_constant;
+            first_level + second_level + third_level
+        };
+
+        if self.config.ladder.is_some() || self.config.network.is_some() || self.config.abr.is_some() || self.config.prefetch.is_some() {
+            self.transfers()?;
+        }
+
+        // the panel does not depend on the cache level, only on the time of the frame
+        let frames = self.hit_list_for_soc.len() as f64;
+        self.display_pc = self.hit_list_for_soc.iter().map(|hit| self.get_display_power_constant(hit.index)).sum::<f64>() / frames;
 
 //        println!("{:?}", self.get_hit_ratios());
+        Ok(())
+    }
+
+    // Bytes of every level and the level three quality of every segment, from the playback over
+    // the network when there is one, which the radio power then follows instead of the wifi
+    // constants; with a ladder alone the wifi power follows the bytes sent. Without a buffer every
+    // segment is requested when it starts playing and needed before it ends; the radio energy of
+    // stalls is spread over the played frames. Prefetched levels no frame needed are wasted.
+    fn transfers(&mut self) -> Result<(), SimulatorError> {
+        let segments = self.segment_fetches()?;
+        let fetches: Vec<SegmentFetch> = segments.iter().map(|s| s.fetch.clone()).collect();
+        let abr = self.config.abr.as_ref();
+        let playback = match self.config.network {
+            Some(ref network) => match network.buffer {
+                Some(ref buffer) => network.play(buffer, &fetches, abr),
+                None => network.stream(&fetches, abr),
+            },
+            // level three at its best quality, a policy needs a network
+            None => {
+                let qualities = fetches.iter().map(|fetch| if fetch.fallback_bytes.is_empty() { None } else { Some(0) }).collect();
+                Playback { qualities, ..Playback::default() }
+            }
+        };
+
+        self.level_bytes = [0.0; 3];
+        self.full_bytes = 0.0;
+        self.prefetch = PrefetchReport::default();
+        let mut segment_bytes = vec![];
+        for (segment, quality) in segments.iter().zip(&playback.qualities) {
+            let level_three = quality.map_or(0.0, |q| segment.fetch.fallback_bytes[q]);
+            segment_bytes.push(segment.sent[0] + segment.sent[1] + level_three);
+            self.level_bytes[0] += segment.sent[0];
+            self.level_bytes[1] += segment.sent[1];
+            self.level_bytes[2] += level_three;
+            self.full_bytes += segment.sent[2];
+            if let Some(level) = self.prefetched_level(segment.index) {
+                let prefetched = if level == 1 { segment.sent[1] } else { level_three };
+                self.prefetch.segments += 1.0;
+                if segment.wasted_frames == segment.frames {
+                    self.prefetch.wasted_segments += 1.0;
+                }
+                self.prefetch.wasted_bytes += prefetched * segment.wasted_frames as f64 / segment.frames as f64;
+            }
+        }
+        let sizes = self.level_three_sizes();
+        let chosen: Vec<usize> = playback.qualities.iter().filter_map(|&q| q).collect();
+        self.level_three_height = if chosen.is_empty() {
+            0.0
+        } else {
+            chosen.iter().map(|&q| sizes[q].height as f64).sum::<f64>() / chosen.len() as f64
+        };
+        self.level_three_switches = chosen.windows(2).filter(|w| w[0] != w[1]).count();
+
+        if self.config.abr.is_some() {
+            // level three frames decode and reproject the quality of their segment, not the full frame
+            self.level_three_qualities = segments.iter().zip(&playback.qualities)
+                .filter_map(|(s, q)| q.map(|q| (s.index, q)))
+                .collect();
+            let video_name = self.video.name();
+            let frames = self.hit_list_for_soc.len() as f64;
+            let mut level_three = SocComponents::default();
+            for hit in self.hit_list_for_soc.iter().filter(|hit| matches!(hit.cache_level, CacheLevel::LevelThree)) {
+                level_three = level_three.add(&self.get_level_three_components(video_name, hit.index / self.config.segment)?);
+            }
+            let full = self.get_soc_level_components(video_name, CacheLevel::LevelThree)?.scale(-self.get_hit_ratios()[2]);
+            let change = level_three.scale(1.0 / frames).add(&full);
+            self.soc_components = self.soc_components.add(&change);
+            self.soc_pc += change.total();
+        }
+
+        if let Some(ref network) = self.config.network {
+            let duration = self.hit_list_for_soc.len() as f64 / self.video.frame_rate();
+            let transfers: Vec<Transfer> = playback.transfers.iter().map(|t| t.1).collect();
+            let (report, energies) = network.simulate(&transfers, playback.end);
+            if duration > 0.0 {
+                self.wifi_pc = energies.iter().sum::<f64>() / duration;
+            }
+            self.network = NetworkReport {
+                startup_latency: playback.startup_latency,
+                stalls: playback.stalls as f64,
+                stall_time: playback.stall_time,
+                ..report
+            };
+            let mut segment_energies = vec![0.0; segments.len()];
+            for (&(i, _), energy) in playback.transfers.iter().zip(energies) {
+                segment_energies[i] += energy;
+            }
+            self.segment_wifi = segments.iter().zip(segment_energies)
+                .map(|(s, energy)| (s.index, energy / s.fetch.duration))
+                .collect();
+        } else if self.config.ladder.is_some() {
+            // The 360 wifi constant streams the full 360 video, so every byte sent costs its share
+            // of that power over the bytes of the full video.
+            let frames = self.hit_list_for_soc.len() as f64;
+            let byte_power = if self.full_bytes > 0.0 {
+                self.get_wifi_power_constant(self.video.name(), CacheLevel::LevelThree)? * frames / self.full_bytes
+            } else {
+                0.0
+            };
+            self.segment_wifi = segments.iter().zip(&segment_bytes)
+                .map(|(s, bytes)| (s.index, byte_power * bytes / s.frames as f64))
+                .collect();
+            self.wifi_pc = byte_power * segment_bytes.iter().sum::<f64>() / frames;
+        }
+
+        // the wifi energy goes to the bytes evenly, those prefetched for nothing included
+        let sent: f64 = segment_bytes.iter().sum();
+        if sent > 0.0 {
+            self.prefetch.wasted_pc = self.wifi_pc * self.prefetch.wasted_bytes / sent;
+        }
+        Ok(())
+    }
+
+    // power of the display when showing frame `index`, 0 without a display model
+    fn get_display_power_constant(&self, index: usize) -> f64 {
+        match self.config.display {
+            Some(ref display) => display.power(index as f64 / self.video.frame_rate()),
+            None => 0.0,
+        }
     }
 
     pub fn print_power_consumption(&self) {
         println!("{} {}", self.wifi_pc, self.soc_pc);
     }
 
+    pub fn get_user_file(&self) -> &str {
+        self.user.user_file()
+    }
+
     pub fn get_wifi_pc(&self) -> f64 {
         self.wifi_pc
     }
@@ -545,6 +833,33 @@ impl Simulator {
         self.soc_pc
     }
 
+    pub fn get_soc_components(&self) -> SocComponents {
+        self.soc_components
+    }
+
+    // none without a ladder or a network
+    pub fn get_level_bytes(&self) -> Option<[f64; 3]> {
+        if self.config.ladder.is_some() || self.config.network.is_some() {
+            Some(self.level_bytes)
+        } else {
+            None
+        }
+    }
+
+    // none without prefetching
+    pub fn get_prefetch(&self) -> Option<PrefetchReport> {
+        self.config.prefetch.map(|_| self.prefetch)
+    }
+
+    // none without a network
+    pub fn get_network(&self) -> Option<NetworkReport> {
+        self.config.network.as_ref().map(|_| self.network)
+    }
+
+    pub fn get_display_pc(&self) -> f64 {
+        self.display_pc
+    }
+
     pub fn get_segment_resend_cnt(&self) -> usize {
         self.segment_resend_counter
     }
@@ -0,0 +1,110 @@
+use config::SimulatorConfig;
+use dataset::Dataset;
+use error::SimulatorError;
+use report::SimulationReport;
+use simulator::Simulator;
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+// number of workers when none is given, one per available cpu
+pub fn default_workers() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+// Apply `f` to every item on `workers` threads. Workers take the next item as soon as they are
+// done with the previous one; the results are returned in the order of `items`.
+pub fn parallel_map<T, R, F>(items: &[T], workers: usize, f: F) -> Vec<R>
+    where T: Sync, R: Send, F: Fn(&T) -> R + Sync {
+    let workers = workers.max(1).min(items.len());
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers).map(|_| scope.spawn(|| {
+            let mut done = vec![];
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                match items.get(index) {
+                    Some(item) => done.push((index, f(item))),
+                    None => return done,
+                }
+            }
+        })).collect();
+        handles.into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    });
+    results.sort_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+// One simulation of a sweep: user `user` of video `video` with the parameter point `config`,
+// each an index into the slices the sweep was built from.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Job {
+    pub video: usize,
+    pub config: usize,
+    pub user: usize,
+}
+
+// Every user of every video simulated with every parameter point, spread over worker threads.
+// Jobs are ordered by video, then parameter point, then user, and the results keep that order
+// whatever the number of workers, so the output of a sweep does not depend on scheduling.
+pub struct Sweep<'a> {
+    datasets: &'a [Dataset],
+    configs: &'a [SimulatorConfig],
+    workers: usize,
+    timelines: bool,
+}
+
+impl<'a> Sweep<'a> {
+    pub fn new(datasets: &'a [Dataset], configs: &'a [SimulatorConfig]) -> Self {
+        Sweep {
+            datasets,
+            configs,
+            workers: default_workers(),
+            timelines: false,
+        }
+    }
+
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    // keep the timeline of every user in its report
+    pub fn timelines(mut self, timelines: bool) -> Self {
+        self.timelines = timelines;
+        self
+    }
+
+    pub fn jobs(&self) -> Vec<Job> {
+        let mut jobs = vec![];
+        for (video, dataset) in self.datasets.iter().enumerate() {
+            for config in 0..self.configs.len() {
+                for user in 0..dataset.users.len() {
+                    jobs.push(Job { video, config, user });
+                }
+            }
+        }
+        jobs
+    }
+
+    // run every job, returning the reports in job order
+    pub fn run(&self) -> Vec<(Job, Result<SimulationReport, SimulatorError>)> {
+        let datasets = self.datasets;
+        let configs = self.configs;
+        let timelines = self.timelines;
+        parallel_map(&self.jobs(), self.workers, |&job| {
+            let dataset = &datasets[job.video];
+            let mut simulator = Simulator::new(&dataset.video, &dataset.users[job.user], configs[job.config].clone());
+            let result = simulator.simulate().and_then(|mut report| {
+                if timelines {
+                    report.timeline = Some(simulator.timeline()?);
+                }
+                Ok(report)
+            });
+            (job, result)
+        })
+    }
+}
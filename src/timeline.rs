@@ -0,0 +1,114 @@
+use output::Row;
+use report::{SimulationParameters, SimulationReport};
+
+// Power spent on one frame of a user, in the unit of the power constants. Averaging `wifi_pc`
+// and `soc_pc` over the frames gives the power of the report, up to the last partial segment
+// without level two.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrameEnergy {
+    pub frame: usize,
+    pub segment_index: usize,
+    // cache level the frame was served from, 1 to 3
+    pub level: usize,
+    // cover ratio of the served tile over the user viewport
+    pub ratio: f64,
+    // object path the tile follows, `path_changed` when it differs from the previous frame
+    pub path: usize,
+    pub path_changed: bool,
+    // top left corner of the user viewport, to follow the head motion
+    pub user_x: i32,
+    pub user_y: i32,
+    // whether the segment of the frame had to be sent again from the server
+    pub resent: bool,
+    pub wifi_pc: f64,
+    pub soc_pc: f64,
+    pub display_pc: f64,
+}
+
+// Power of one segment, the mean over its frames.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SegmentEnergy {
+    pub segment_index: usize,
+    pub first_frame: usize,
+    pub frames: usize,
+    pub resent: bool,
+    pub wifi_pc: f64,
+    pub soc_pc: f64,
+    pub display_pc: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Timeline {
+    pub frames: Vec<FrameEnergy>,
+    pub segments: Vec<SegmentEnergy>,
+}
+
+impl Timeline {
+    pub(crate) fn new(frames: Vec<FrameEnergy>) -> Self {
+        let mut segments: Vec<SegmentEnergy> = vec![];
+        for frame in &frames {
+            if segments.last().is_none_or(|s| s.segment_index != frame.segment_index) {
+                segments.push(SegmentEnergy {
+                    segment_index: frame.segment_index,
+                    first_frame: frame.frame,
+                    frames: 0,
+                    resent: frame.resent,
+                    wifi_pc: 0.0,
+                    soc_pc: 0.0,
+                    display_pc: 0.0,
+                });
+            }
+            let segment = segments.last_mut().unwrap();
+            segment.frames += 1;
+            segment.wifi_pc += frame.wifi_pc;
+            segment.soc_pc += frame.soc_pc;
+            segment.display_pc += frame.display_pc;
+        }
+        for segment in &mut segments {
+            segment.wifi_pc /= segment.frames as f64;
+            segment.soc_pc /= segment.frames as f64;
+            segment.display_pc /= segment.frames as f64;
+        }
+        Timeline { frames, segments }
+    }
+}
+
+// One frame of a timeline together with the user and parameters it belongs to, so timelines of
+// several users and parameter points can share a file.
+#[derive(Debug, Serialize)]
+pub struct TimelineRow<'a> {
+    pub video: &'a str,
+    pub user: &'a str,
+    pub parameters: &'a SimulationParameters,
+    #[serde(flatten)]
+    pub frame: &'a FrameEnergy,
+}
+
+impl<'a> TimelineRow<'a> {
+    pub fn rows(report: &'a SimulationReport, timeline: &'a Timeline) -> Vec<TimelineRow<'a>> {
+        timeline.frames.iter().map(|frame| TimelineRow {
+            video: &report.video,
+            user: &report.user,
+            parameters: &report.parameters,
+            frame,
+        }).collect()
+    }
+}
+
+impl<'a> Row for TimelineRow<'a> {
+    fn columns() -> &'static [&'static str] {
+        &["video", "user", "threshold", "segment", "l1", "l2", "opt", "frame", "segment_index", "level", "ratio",
+          "path", "path_changed", "user_x", "user_y", "resent", "wifi_pc", "soc_pc",
+          "display_pc"]
+    }
+
+    fn values(&self) -> Vec<String> {
+        let p = self.parameters;
+        let f = self.frame;
+        vec![self.video.to_string(), self.user.to_string(), p.threshold.to_string(), p.segment.to_string(),
+             p.level_one.to_string(), p.level_two.to_string(), p.opt.to_string(), f.frame.to_string(),
+             f.segment_index.to_string(), f.level.to_string(), f.ratio.to_string(), f.path.to_string(),
+             f.path_changed.to_string(), f.user_x.to_string(), f.user_y.to_string(), f.resent.to_string(),
+             f.wifi_pc.to_string(), f.soc_pc.to_string(), f.display_pc.to_string()]
+    }
+}
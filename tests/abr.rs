@@ -0,0 +1,89 @@
+extern crate serde_json;
+extern crate simulator;
+
+mod common;
+
+use common::{builder, network, paris};
+use simulator::{AbrModel, AbrPolicy, AbrState, BufferBased, ConfigError, Mpc, NetworkModel, PlaybackBuffer,
+                ThroughputBased};
+
+// 4, 2 and 1 MB a second of video
+const BYTES: [f64; 3] = [4e6, 2e6, 1e6];
+
+fn state(buffer: f64, throughput: Option<f64>) -> AbrState<'static> {
+    AbrState { segment: 3, bytes: &BYTES, duration: 1.0, buffer, throughput, last: Some(0) }
+}
+
+#[test]
+fn test_policies() {
+    let bba = BufferBased { reservoir: 2.0, cushion: 4.0 };
+    assert_eq!(bba.choose(&state(1.0, None)), 2);
+    // halfway through the cushion the rate may reach 2.5 MB/s
+    assert_eq!(bba.choose(&state(4.0, None)), 1);
+    assert_eq!(bba.choose(&state(6.0, None)), 0);
+
+    let rate = ThroughputBased { safety: 0.8 };
+    assert_eq!(rate.choose(&state(0.0, None)), 2);
+    assert_eq!(rate.choose(&state(0.0, Some(3e6))), 1);
+    assert_eq!(rate.choose(&state(0.0, Some(1e6))), 2);
+
+    // a second of stall weighs as much as the best bitrate: without buffer the lowest quality stalls
+    // the least, with enough of it the best one pays off
+    let mpc = Mpc { horizon: 2, rebuffer_penalty: 32.0, switch_penalty: 1.0 };
+    assert_eq!(mpc.choose(&state(0.0, Some(1e6))), 2);
+    assert_eq!(mpc.choose(&state(10.0, Some(4e6))), 0);
+}
+
+#[test]
+fn test_abr_model() {
+    let model: AbrModel = serde_json::from_str(r#"{"qualities": ["3840x2160", "1920x1080"],
+        "policy": {"type": "throughput", "safety": 0.9}}"#).unwrap();
+    assert_eq!(model.qualities.len(), 2);
+    assert!(model.abr().is_ok());
+
+    let fixed: AbrModel = serde_json::from_str(r#"{"qualities": ["3840x2160"], "policy": {"type": "fixed", "quality": 1}}"#)
+        .unwrap();
+    match fixed.abr() {
+        Err(ConfigError::InvalidAbr(_)) => {}
+        other => panic!("expected an invalid abr, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_abr_lowers_level_three() {
+    let dataset = paris();
+    let network = NetworkModel { bandwidth: 20.0, rtt: 20.0, ..network(Some(PlaybackBuffer { segments: 3, startup: 1.0 })) };
+    let builder = builder().network(network);
+    let model = |policy: &str| -> AbrModel {
+        serde_json::from_str(&format!(r#"{{"qualities": ["3840x2160", "2560x1440", "1280x720"], "policy": {}}}"#, policy))
+            .unwrap()
+    };
+    let simulate = |model: AbrModel| common::simulate(&dataset, builder.clone().abr(model.abr().unwrap()));
+    let best = common::simulate(&dataset, builder.clone());
+    let fixed = simulate(model(r#"{"type": "fixed", "quality": 0}"#));
+    let lowest = simulate(model(r#"{"type": "fixed", "quality": 2}"#));
+    let adaptive = simulate(model(r#"{"type": "buffer", "reservoir": 0.5, "cushion": 2}"#));
+
+    // the best quality is the full frame, the lowest a ninth of it
+    assert_eq!(fixed.level_bytes, best.level_bytes);
+    assert_eq!((fixed.level_three_height, best.level_three_height), (Some(2160.0), None));
+    assert!((lowest.level_bytes.unwrap()[2] * 9.0 - best.level_bytes.unwrap()[2]).abs() < 1e-3);
+    assert_eq!((lowest.level_three_height, lowest.level_three_switches), (Some(720.0), Some(0)));
+    assert!(lowest.network.unwrap().stall_time < best.network.unwrap().stall_time);
+    assert!(lowest.wifi_pc < best.wifi_pc);
+    let height = adaptive.level_three_height.unwrap();
+    assert!((720.0..=2160.0).contains(&height));
+    // a lower quality reprojects fewer pixels, and sends fewer bytes
+    assert!((fixed.soc_pc - best.soc_pc).abs() < 1e-9);
+    assert!(lowest.soc_pc < best.soc_pc);
+    assert!(lowest.soc_components.reproject < best.soc_components.reproject);
+    assert!(lowest.energy.soc_joules + lowest.energy.wifi_joules < best.energy.soc_joules + best.energy.wifi_joules);
+    assert!(adaptive.soc_pc >= lowest.soc_pc && adaptive.soc_pc <= best.soc_pc);
+
+    // without a network a policy has no buffer nor throughput to choose on
+    let model = model(r#"{"type": "fixed", "quality": 0}"#);
+    match common::builder().abr(model.abr().unwrap()).build() {
+        Err(ConfigError::InvalidAbr(_)) => {}
+        other => panic!("expected an invalid abr, got {:?}", other.map(|_| ())),
+    }
+}
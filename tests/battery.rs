@@ -0,0 +1,36 @@
+extern crate simulator;
+
+use simulator::{Battery, ConfigError, DischargePoint};
+
+#[test]
+fn test_linear_discharge() {
+    // 10 Wh drawn at 5 W last two hours
+    let battery = Battery::new(10.0, 0.0, vec![]).unwrap();
+    let discharge = battery.discharge(5.0);
+    assert!((discharge.playback_time() - 7200.0).abs() < 1e-6);
+    assert!((discharge.charge_at(3600.0) - 0.5).abs() < 1e-9);
+    assert_eq!(discharge.charge_at(10000.0), 0.0);
+
+    let with_cutoff = Battery::new(10.0, 0.1, vec![]).unwrap().discharge(5.0);
+    assert!((with_cutoff.playback_time() - 6480.0).abs() < 1e-6);
+    assert!(Battery::new(10.0, 0.0, vec![]).unwrap().discharge(0.0).playback_time().is_infinite());
+}
+
+#[test]
+fn test_discharge_curve() {
+    let curve = vec![DischargePoint { charge: 0.5, efficiency: 0.8 }, DischargePoint { charge: 1.0, efficiency: 1.0 }];
+    let battery = Battery::new(10.0, 0.0, curve).unwrap();
+    assert_eq!(battery.efficiency(0.75), 0.9);
+    assert_eq!(battery.efficiency(0.2), 0.8);
+
+    // the first half is 0.9 efficient on average, the second 0.8
+    let discharge = battery.discharge(5.0);
+    assert!((discharge.playback_time() - 3600.0 * (0.9 + 0.8)).abs() < 1e-6);
+    assert!(discharge.points().windows(2).all(|w| w[0].charge > w[1].charge && w[0].time < w[1].time));
+
+    match Battery::new(10.0, 0.0, vec![DischargePoint { charge: 0.5, efficiency: 0.0 }]) {
+        Err(ConfigError::InvalidBattery(_)) => {}
+        other => panic!("expected an invalid battery, got {:?}", other),
+    }
+    assert!(Battery::new(0.0, 0.0, vec![]).is_err());
+}
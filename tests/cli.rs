@@ -0,0 +1,97 @@
+extern crate simulator;
+
+use simulator::parse;
+use simulator::{CliError, Command, FitOptions, Mode, OptimizeVersion, PowerSource, Size};
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(|s| s.to_string()).collect()
+}
+
+const VIDEO: &str = "--video Paris --users u --tracedump t.txt --cluster c.json";
+
+#[test]
+fn test_parse_simulate() {
+    let command = parse(&args(&format!("simulate hit {} --threshold 0.9 --l1 1200x1000 --l2=2400x2160 --opt O1", VIDEO))).unwrap();
+    match command {
+        Command::Simulate(Mode::Hit, options) => {
+            assert_eq!(options.video, "Paris");
+            assert_eq!(options.users, "u");
+            assert_eq!(options.power, PowerSource::Files {
+                power_360: "power_4k_360.json".to_string(),
+                power_1080p: "power_1080p.json".to_string(),
+            });
+            assert_eq!(options.grid.thresholds, vec![0.9]);
+            assert_eq!(options.grid.level_ones, vec![Size { width: 1200, height: 1000 }]);
+            assert_eq!(options.grid.level_twos, vec![Size { width: 2400, height: 2160 }]);
+            assert_eq!(options.grid.opts, vec![OptimizeVersion::O1]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_sweep_lists() {
+    match parse(&args(&format!("sweep {} --threshold 0.8,0.9,1.0 --segment 10,20", VIDEO))).unwrap() {
+        Command::Sweep(options) => {
+            assert_eq!(options.grid.point_count(), 6);
+            assert_eq!(options.jobs, None);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_errors_name_the_argument() {
+    assert_eq!(parse(&args("simulate power --users u --tracedump t.txt")).unwrap_err(),
+               CliError::MissingArgument("--video"));
+    assert_eq!(parse(&args("simulate power --video Paris --users u --tracedump t.txt")).unwrap_err(),
+               CliError::MissingArgument("--cluster"));
+    assert_eq!(parse(&args(&format!("compare {} --threshold 0.8,0.9", VIDEO))).unwrap_err(),
+               CliError::NotAList { argument: "--threshold".to_string(), command: "compare" });
+    assert_eq!(parse(&args(&format!("sweep {} --segment", VIDEO))).unwrap_err(),
+               CliError::MissingValue("--segment".to_string()));
+    match parse(&args(&format!("sweep {} --jobs=4", VIDEO))).unwrap() {
+        Command::Sweep(options) => assert_eq!(options.jobs, Some(4)),
+        other => panic!("unexpected {:?}", other),
+    }
+    match parse(&args(&format!("sweep {} --jobs 0", VIDEO))).unwrap_err() {
+        CliError::InvalidValue { argument, .. } => assert_eq!(argument, "--jobs"),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(parse(&args(&format!("simulate power {} --battery b.json", VIDEO))).unwrap_err(),
+               CliError::MissingArgument("--battery-output"));
+    assert_eq!(parse(&args(&format!("simulate power {} --bandwidth-trace lte.trace", VIDEO))).unwrap_err(),
+               CliError::MissingArgument("--network"));
+    match parse(&args(&format!("simulate power {} --opt O2", VIDEO))).unwrap_err() {
+        CliError::InvalidValue { argument, value, .. } => {
+            assert_eq!(argument, "--opt");
+            assert_eq!(value, "O2");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_power_profiles() {
+    match parse(&args(&format!("simulate power {} --power-profiles profiles.json --device tx2", VIDEO))).unwrap() {
+        Command::Simulate(Mode::Power, options) => assert_eq!(options.power, PowerSource::Bundle {
+            path: "profiles.json".to_string(),
+            device: "tx2".to_string(),
+        }),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(parse(&args(&format!("simulate power {} --power-profiles profiles.json", VIDEO))).unwrap_err(),
+               CliError::MissingArgument("--device"));
+    assert_eq!(parse(&args(&format!("simulate power {} --power-profiles p.json --device tx2 --power-360 a.json", VIDEO))).unwrap_err(),
+               CliError::Conflict("--power-profiles", "--power-360"));
+}
+
+#[test]
+fn test_parse_fit() {
+    assert_eq!(parse(&args("fit --log a.csv --log=b.csv --labels labels.csv")).unwrap(), Command::Fit(FitOptions {
+        logs: vec!["a.csv".to_string(), "b.csv".to_string()],
+        labels: "labels.csv".to_string(),
+        output: None,
+    }));
+    assert_eq!(parse(&args("fit --labels labels.csv")).unwrap_err(), CliError::MissingArgument("--log"));
+}
@@ -0,0 +1,39 @@
+// Fixtures shared by the integration tests; every test file uses only some of them.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use simulator::{Dataset, NetworkModel, PlaybackBuffer, PowerProfile, RadioPower, SimulationReport, Simulator,
+                SimulatorConfig, SimulatorConfigBuilder, VideoBitrate};
+
+pub fn paris() -> Dataset {
+    Dataset::load("Paris", "user_viewport_result/Paris-sJxiPiAaB4k",
+                  "tracedump/paris.txt", "object_as_cluster/paris.json").unwrap()
+}
+
+pub fn profile() -> PowerProfile {
+    PowerProfile::from_files("power_4k_360.json", "power_1080p.json").unwrap()
+}
+
+// a builder with the default power constants
+pub fn builder() -> SimulatorConfigBuilder {
+    SimulatorConfig::builder().power_profile(profile())
+}
+
+// 1 MB takes a second over its 8 Mbit/s, plus a 100 ms round trip
+pub fn network(buffer: Option<PlaybackBuffer>) -> NetworkModel {
+    NetworkModel {
+        bandwidth: 8.0,
+        rtt: 100.0,
+        bitrate: VideoBitrate { normal: 8.0, panorama: 40.0 },
+        videos: HashMap::new(),
+        radio: RadioPower { active: 1000.0, tail: 500.0, idle: 10.0, tail_time: 0.5 },
+        buffer,
+        trace: None,
+    }
+}
+
+// simulates the first user of `dataset`
+pub fn simulate(dataset: &Dataset, builder: SimulatorConfigBuilder) -> SimulationReport {
+    Simulator::new(&dataset.video, &dataset.users[0], builder.build().unwrap()).simulate().unwrap()
+}
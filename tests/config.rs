@@ -0,0 +1,39 @@
+extern crate simulator;
+
+use simulator::{SimulatorConfig, SimulatorConfigBuilder, ConfigError, Size};
+
+fn builder() -> SimulatorConfigBuilder {
+    SimulatorConfig::builder()
+}
+
+#[test]
+fn test_default_config_is_valid() {
+    assert!(builder().build().is_ok());
+}
+
+#[test]
+fn test_threshold_out_of_range() {
+    assert_eq!(builder().threshold(1.2).build().unwrap_err(), ConfigError::ThresholdOutOfRange(1.2));
+    assert!(builder().threshold(0.0).build().is_ok());
+    assert!(builder().threshold(1.0).build().is_ok());
+}
+
+#[test]
+fn test_zero_segment() {
+    assert_eq!(builder().segment(0).build().unwrap_err(), ConfigError::ZeroSegment);
+}
+
+#[test]
+fn test_level_two_smaller_than_level_one() {
+    let level_one = Size::new(1440, 1440);
+    let level_two = Size::new(2400, 1200);
+    let result = builder().level_one(level_one).level_two(level_two).build();
+    assert_eq!(result.unwrap_err(), ConfigError::LevelTwoSmallerThanLevelOne { level_one, level_two });
+}
+
+#[test]
+fn test_larger_than_panorama() {
+    let size = Size { width: 2160, height: 3840 };
+    let result = builder().level_one(Size::new(1440, 1440)).level_two(size).build();
+    assert_eq!(result.unwrap_err(), ConfigError::LargerThanPanorama { level: "level two", size });
+}
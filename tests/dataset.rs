@@ -0,0 +1,28 @@
+extern crate simulator;
+
+use simulator::{Dataset, PowerProfile, Simulator, SimulatorConfig, Size};
+
+#[test]
+fn test_dataset_drives_many_simulators() {
+    let dataset = Dataset::load("Rollercoaster", "user_viewport_result/Rollercoaster-8lsB-P8nGSM",
+                                "tracedump/roller.txt", "object_as_cluster/roller.json").unwrap();
+    assert!(dataset.video.frame_count() > 0);
+    assert!(!dataset.users.is_empty());
+    assert!(dataset.skipped.is_empty());
+
+    let user = &dataset.users[0];
+    let mut hit_ratios = vec![];
+    for &threshold in &[0.8, 1.0] {
+        let config = SimulatorConfig::builder()
+            .threshold(threshold)
+            .level_one(Size::new(1440, 1440))
+            .power_profile(PowerProfile::from_files("power_4k_360.json", "power_1080p.json").unwrap())
+            .build()
+            .unwrap();
+        let mut simulator = Simulator::new(&dataset.video, user, config);
+        simulator.simulate().unwrap();
+        hit_ratios.push(simulator.get_hit_ratios()[0]);
+    }
+    // a stricter threshold can only lower the level one hit ratio
+    assert!(hit_ratios[0] >= hit_ratios[1]);
+}
@@ -1,3 +1,12 @@
 extern crate simulator;
 
 use simulator::Viewport;
+
+#[test]
+fn test_cover_result_across_panorama_seam() {
+    let user_fov = Viewport::new(100, 3600, 800, 480, 400);
+    assert_eq!(user_fov.get_cover_result(&user_fov).unwrap(), 1.0);
+
+    let far_away = Viewport::new(100, 1200, 800, 480, 400);
+    assert_eq!(far_away.get_cover_result(&user_fov).unwrap(), 0.0);
+}
@@ -0,0 +1,91 @@
+extern crate simulator;
+
+use simulator::{Dataset, PowerConstants, PowerProfile, Simulator, SimulatorConfig, SimulatorError, UserTrace};
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("vros-simulate-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_parse_error_names_file_and_line() {
+    let dir = temp_dir("parse");
+    let user_file = dir.join("uid-bad.txt");
+    fs::write(&user_file, "0 100 408,624,1224,1200\n1 100 408,abc,1224,1200\n").unwrap();
+
+    match UserTrace::load(user_file.to_str().unwrap()) {
+        Err(SimulatorError::Parse { file, line, .. }) => {
+            assert_eq!(file, user_file.to_str().unwrap());
+            assert_eq!(line, 2);
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_empty_trace() {
+    let dir = temp_dir("empty");
+    let user_file = dir.join("uid-empty.txt");
+    fs::write(&user_file, "").unwrap();
+
+    match UserTrace::load(user_file.to_str().unwrap()) {
+        Err(SimulatorError::EmptyTrace { file }) => assert_eq!(file, user_file.to_str().unwrap()),
+        other => panic!("expected an empty trace, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_dataset_skips_corrupt_user() {
+    let dir = temp_dir("skip");
+    fs::copy("user_viewport_result/Rollercoaster-8lsB-P8nGSM/uid-00d6d7f2-23df-4062-84dd-d5e99183dae1.txt",
+             dir.join("uid-good.txt")).unwrap();
+    fs::write(dir.join("uid-bad.txt"), "0 100 408,624\n").unwrap();
+
+    let dataset = Dataset::load("Rollercoaster", &dir, "tracedump/roller.txt", "object_as_cluster/roller.json").unwrap();
+    assert_eq!(dataset.users.len(), 1);
+    assert_eq!(dataset.skipped.len(), 1);
+
+    // without a user left there is nothing to average over
+    fs::remove_file(dir.join("uid-good.txt")).unwrap();
+    match Dataset::load("Rollercoaster", &dir, "tracedump/roller.txt", "object_as_cluster/roller.json") {
+        Err(SimulatorError::NoUsers { skipped, .. }) => assert_eq!(skipped.len(), 1),
+        other => panic!("expected no users, got {:?}", other),
+    }
+    let empty = temp_dir("no-users");
+    match Dataset::load("Rollercoaster", &empty, "tracedump/roller.txt", "object_as_cluster/roller.json") {
+        Err(SimulatorError::NoUsers { dir, skipped }) => assert_eq!((dir, skipped.len()), (empty.to_string_lossy().into_owned(), 0)),
+        other => panic!("expected no users, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_missing_power_constant() {
+    let dataset = Dataset::load("Rollercoaster", "user_viewport_result/Rollercoaster-8lsB-P8nGSM",
+                                "tracedump/roller.txt", "object_as_cluster/roller.json").unwrap();
+    let config = SimulatorConfig::builder().build().unwrap();
+    let mut simulator = Simulator::new(&dataset.video, &dataset.users[0], config);
+    match simulator.simulate() {
+        Err(SimulatorError::UnknownVideo { video }) => assert_eq!(video, "Rollercoaster"),
+        other => panic!("expected an unknown video, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_incomplete_power_constants() {
+    let missing = PowerProfile::new(vec![PowerConstants::new("Paris_WIFI", 326.5)], vec![]);
+    match missing {
+        Err(SimulatorError::MissingPowerConstant { name }) => assert_eq!(name, "Paris_SOC"),
+        other => panic!("expected a missing power constant, got {:?}", other),
+    }
+    let unknown = PowerProfile::new(vec![PowerConstants::new("Paris_WIFI", 326.5), PowerConstants::new("Paris_GPU", 1.0)], vec![]);
+    match unknown {
+        Err(SimulatorError::UnknownPowerConstant { name, .. }) => assert_eq!(name, "Paris_GPU"),
+        other => panic!("expected an unknown power constant, got {:?}", other),
+    }
+}
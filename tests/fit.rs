@@ -0,0 +1,57 @@
+extern crate simulator;
+
+use simulator::{fit, read_labels, read_power_consumption_from_file, write_power_consumption, PowerLog,
+                PowerProfile, SimulatorError};
+
+use std::env;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+fn temp_file(name: &str, contents: &str) -> String {
+    let path: PathBuf = env::temp_dir().join(format!("vros-simulate-fit-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_fit_power_constants() {
+    // wifi ramps from 100 to 300 mW, the soc rails are flat
+    let log = temp_file("log.csv", "timestamp,rail,mW\n\
+        0,VDD_WIFI,100\n10,VDD_WIFI,300\n\
+        0,VDD_SOC,1000\n10,VDD_SOC,1000\n\
+        0,VDD_GPU,500\n5,VDD_GPU,500\n10,VDD_GPU,500\n");
+    let labels = temp_file("labels.csv", "name,rail,start,end\n\
+        Paris_WIFI,VDD_WIFI,0,10\n\
+        Paris_SOC,VDD_SOC,2,8\n\
+        Paris_SOC,VDD_GPU,2,8\n\
+        Paris_DECODE,VDD_GPU,5,10\n\
+        Paris_DECODE,VDD_GPU,0,4\n");
+    let log = PowerLog::load(&[log]).unwrap();
+    assert_eq!(log.energy("VDD_WIFI", 0.0, 5.0), Some(750.0));
+
+    let constants = fit(&log, &read_labels(&labels).unwrap()).unwrap();
+    let fitted: Vec<(&str, f64)> = constants.iter().map(|c| (c.name(), c.value())).collect();
+    assert_eq!(fitted, vec![("Paris_WIFI", 200.0), ("Paris_SOC", 1500.0), ("Paris_DECODE", 500.0)]);
+
+    // the output reads back as power constants
+    let output = env::temp_dir().join(format!("vros-simulate-fit-{}-out.json", std::process::id()));
+    write_power_consumption(File::create(&output).unwrap(), &constants).unwrap();
+    let read = read_power_consumption_from_file(&output).unwrap();
+    let profile = PowerProfile::new(read.clone(), read).unwrap();
+    assert_eq!(profile.get_1080p("Paris").unwrap().soc, 1500.0);
+    assert_eq!(profile.get_1080p("Paris").unwrap().decode, Some(500.0));
+}
+
+#[test]
+fn test_fit_outside_the_logs() {
+    let log = PowerLog::load(&[temp_file("short.csv", "0,VDD_WIFI,100\n1,VDD_WIFI,100\n")]).unwrap();
+    let labels = read_labels(&temp_file("late.csv", "Paris_WIFI,VDD_WIFI,0,2\n")).unwrap();
+    match fit(&log, &labels) {
+        Err(SimulatorError::MissingSamples { rail, .. }) => assert_eq!(rail, "VDD_WIFI"),
+        other => panic!("expected missing samples, got {:?}", other),
+    }
+    match read_labels(&temp_file("empty.csv", "Paris_WIFI,VDD_WIFI,2,2\n")) {
+        Err(SimulatorError::Parse { line, .. }) => assert_eq!(line, 1),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
@@ -0,0 +1,69 @@
+extern crate simulator;
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::{paris, profile, simulate};
+use simulator::{BitrateLadder, ConfigError, Rung, SegmentSizes, Simulator, SimulatorConfig, SimulatorError, Size,
+                VideoLadder};
+
+fn ladder(video: &str) -> BitrateLadder {
+    let mut videos = HashMap::new();
+    videos.insert(video.to_string(), VideoLadder {
+        rungs: vec![Rung { size: Size::new(3840, 2160), kbps: 40000.0 }, Rung { size: Size::new(1920, 1080), kbps: 8000.0 }],
+        segments: vec![SegmentSizes { size: Size::new(1440, 1440), bytes: vec![2e6, 1e6] }],
+    });
+    BitrateLadder { videos }
+}
+
+#[test]
+fn test_ladder_bitrates() {
+    let ladder = ladder("Paris");
+    let paris = ladder.video("Paris").unwrap();
+    assert_eq!(paris.kbps(Size::new(1920, 1080)), Some(8000.0));
+    // halfway from 1080p to 4k by area, and half of 1080p below the ladder
+    assert_eq!(paris.kbps(Size::new(2400, 2160)), Some(24000.0));
+    assert_eq!(paris.kbps(Size::new(960, 1080)), Some(4000.0));
+
+    // the encoded segments first, then the rungs past them
+    assert_eq!(paris.frame_bytes(Size::new(1440, 1440), 1, 20, 30.0), Some(50000.0));
+    assert_eq!(paris.frame_bytes(Size::new(1440, 1440), 2, 20, 30.0), Some(8000.0 * 1000.0 / 8.0 / 30.0));
+    assert_eq!(VideoLadder { rungs: vec![], segments: vec![] }.kbps(Size::new(1920, 1080)), None);
+}
+
+#[test]
+fn test_ladder_bytes() {
+    let dataset = paris();
+    let profile = profile();
+    let wifi_360 = profile.get_360("Paris").unwrap().wifi;
+    let builder = SimulatorConfig::builder().power_profile(profile);
+    let without = simulate(&dataset, builder.clone());
+    let report = simulate(&dataset, builder.clone().ladder(ladder("Paris")));
+
+    assert_eq!((without.level_bytes, without.full_bytes), (None, None));
+    let (level_bytes, full_bytes) = (report.level_bytes.unwrap(), report.full_bytes.unwrap());
+    // the wifi power follows the bytes, the full 360 video costing the 360 wifi constant; this user
+    // misses so often that level one and the resent segments take more bytes than the full video
+    let savings = 1.0 - level_bytes.iter().sum::<f64>() / full_bytes;
+    assert!((report.wifi_pc - wifi_360 * (1.0 - savings)).abs() < 1e-9);
+    assert!(savings < 0.0 && report.wifi_pc > wifi_360);
+    let frame_4k = 40000.0 * 1000.0 / 8.0 / 30.0;
+    assert!((full_bytes - report.frame_count as f64 * frame_4k).abs() < 1e-3);
+    // level one is 1080p by area past the two encoded segments, resent segments are sent whole
+    let frame_1080p = 8000.0 * 1000.0 / 8.0 / 30.0;
+    assert!((level_bytes[0] - (3e6 + (report.frame_count - 40) as f64 * frame_1080p)).abs() < 1e-3);
+    assert_eq!(level_bytes[1], 0.0);
+    assert!((level_bytes[2] - report.segment_resend_count as f64 * 20.0 * frame_4k).abs() < 1e-3);
+
+    match Simulator::new(&dataset.video, &dataset.users[0], builder.clone().ladder(ladder("Rhino")).build().unwrap()).simulate() {
+        Err(SimulatorError::UnknownBitrate { video, .. }) => assert_eq!(video, "Paris"),
+        other => panic!("expected an unknown bitrate, got {:?}", other),
+    }
+    let mut negative = ladder("Paris");
+    negative.videos.get_mut("Paris").unwrap().rungs[0].kbps = -1.0;
+    match builder.ladder(negative).build() {
+        Err(ConfigError::InvalidLadder(_)) => {}
+        other => panic!("expected an invalid ladder, got {:?}", other),
+    }
+}
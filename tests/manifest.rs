@@ -0,0 +1,32 @@
+extern crate simulator;
+
+use simulator::{Manifest, Mode, OptimizeVersion, PowerSource, Size};
+
+#[test]
+fn test_load_example_manifest() {
+    let manifest = Manifest::load("auto_pc.json").unwrap();
+    assert_eq!(manifest.mode, Mode::Power);
+    assert_eq!(manifest.power, PowerSource::default());
+    assert_eq!(manifest.grid.point_count(), 9);
+    assert_eq!(manifest.grid.level_ones, vec![Size::new(1440, 1440)]);
+    assert_eq!(manifest.videos.len(), 5);
+    assert_eq!(manifest.videos[0].power_key, "Elephant");
+    assert_eq!(manifest.videos[0].output, Some("elephant_power.txt".to_string()));
+}
+
+#[test]
+fn test_manifest_defaults() {
+    let path = std::env::temp_dir().join("simulator_manifest_defaults.json");
+    std::fs::write(&path, r#"{
+        "mode": "hit",
+        "power": {"path": "profiles.json", "device": "tx2"},
+        "grid": {"opt": ["O0", "O1"]},
+        "videos": [{"power_key": "Paris", "users": "u", "tracedump": "t.txt", "cluster": "c.json"}]
+    }"#).unwrap();
+    let manifest = Manifest::load(&path).unwrap();
+    assert_eq!(manifest.mode, Mode::Hit);
+    assert_eq!(manifest.power, PowerSource::Bundle { path: "profiles.json".to_string(), device: "tx2".to_string() });
+    assert_eq!(manifest.grid.opts, vec![OptimizeVersion::O0, OptimizeVersion::O1]);
+    assert!(manifest.grid.thresholds.is_empty());
+    assert_eq!(manifest.videos[0].output, None);
+}
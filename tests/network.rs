@@ -0,0 +1,103 @@
+extern crate simulator;
+
+mod common;
+
+use common::{builder, paris, simulate};
+use simulator::{BandwidthTrace, ConfigError, NetworkModel, PlaybackBuffer, SegmentFetch, Transfer};
+
+fn network() -> NetworkModel {
+    common::network(None)
+}
+
+#[test]
+fn test_radio_states() {
+    // 1 MB takes a second over 8 Mbit/s, plus the round trip
+    let transfer = |request: f64| Transfer { request, deadline: request + 1.5, bytes: 1e6, fallback: request > 1.0 };
+    let transfers = vec![transfer(1.0), transfer(1.5), transfer(5.0)];
+    let (report, energies) = network().simulate(&transfers, 10.0);
+    assert_eq!(report.bytes, 3e6);
+    assert!((report.active_time - 3.3).abs() < 1e-9);
+    // the second transfer waits for the first, then the radio stays in its tail and idles until 5s
+    assert!((report.tail_time - 1.0).abs() < 1e-9);
+    assert!((report.idle_time - 5.7).abs() < 1e-9);
+    assert!((report.active_time + report.tail_time + report.idle_time - 10.0).abs() < 1e-9);
+    // only the second is done after its deadline, at 3.2s
+    assert_eq!((report.late_transfers, report.late_fallbacks), (1.0, 1.0));
+    assert!((report.late_time - 0.2).abs() < 1e-9);
+
+    assert!((energies[0] - (10.0 + 1100.0)).abs() < 1e-9);
+    assert!((energies[1] - (1100.0 + 250.0 + 1.3 * 10.0)).abs() < 1e-9);
+    assert!((energies[2] - (1100.0 + 250.0 + 3.4 * 10.0)).abs() < 1e-9);
+}
+
+#[test]
+fn test_bandwidth_trace() {
+    // 1000 bytes at 100ms and 3000 at 400ms, then again every 500ms
+    let trace = BandwidthTrace::new(vec![(0.4, 3000.0), (0.1, 1000.0), (0.5, 0.0)]).unwrap();
+    assert_eq!(trace.delivered(0.3), 1000.0);
+    assert_eq!(trace.delivered(1.2), 9000.0);
+    assert_eq!(trace.arrival(0.0, 1000.0), 0.1);
+    assert_eq!(trace.arrival(0.2, 3500.0), 0.6);
+    assert_eq!(trace.arrival(0.2, 7001.0), 1.1);
+
+    // the trace replaces the bandwidth, the round trip still delays the start
+    let mut model = network();
+    model.trace = Some(trace);
+    assert_eq!(model.arrival(0.0, 1000.0), 0.4);
+    assert!(BandwidthTrace::new(vec![(0.0, 1000.0)]).is_err());
+    assert!(BandwidthTrace::new(vec![(1.0, -1.0)]).is_err());
+}
+
+#[test]
+fn test_playback_stalls() {
+    // 1 MB segments of a second take 1.1s each, the second one falls back to level three after half a second
+    let segment = |fallback_bytes: Vec<f64>| SegmentFetch { duration: 1.0, bytes: 1e6, fallback_at: 0.5, fallback_bytes, prefetched: false };
+    let segments = vec![segment(vec![]), segment(vec![5e5]), segment(vec![])];
+    let playback = network().play(&PlaybackBuffer { segments: 2, startup: 0.5 }, &segments, None);
+
+    // playback starts with the first segment, stalls 0.1s for the second and 1.2s for its fallback
+    // requested at 2.7s, since the third segment requested when the first ended at 2.1s goes first
+    assert!((playback.startup_latency - 1.1).abs() < 1e-9);
+    assert_eq!(playback.stalls, 2);
+    assert!((playback.stall_time - 1.3).abs() < 1e-9);
+    assert!((playback.end - 5.4).abs() < 1e-9);
+    let order: Vec<(usize, bool)> = playback.transfers.iter().map(|t| (t.0, t.1.fallback)).collect();
+    assert_eq!(order, vec![(0, false), (1, false), (2, false), (1, true)]);
+    assert_eq!(playback.qualities, vec![None, Some(0), None]);
+
+    let (report, _) = network().simulate(&playback.transfers.iter().map(|t| t.1).collect::<Vec<_>>(), playback.end);
+    assert_eq!(report.late_transfers, 2.0);
+    assert_eq!(report.late_fallbacks, 1.0);
+}
+
+#[test]
+fn test_network_replaces_wifi_power() {
+    let dataset = paris();
+    let without = simulate(&dataset, builder());
+    let with = simulate(&dataset, builder().network(network()));
+
+    assert_eq!(with.soc_pc, without.soc_pc);
+    assert_ne!(with.wifi_pc, without.wifi_pc);
+    assert_eq!(without.network, None);
+    let network_report = with.network.unwrap();
+    assert!(network_report.bytes > 0.0);
+    let radio_time = network_report.active_time + network_report.tail_time + network_report.idle_time;
+    assert!(radio_time >= with.energy.duration - 1e-9);
+
+    // every fallback waits for its level three data
+    let buffered = common::network(Some(PlaybackBuffer { segments: 3, startup: 1.0 }));
+    let with_buffer = simulate(&dataset, builder().network(buffered));
+    assert_eq!(with_buffer.soc_pc, without.soc_pc);
+    let buffered_report = with_buffer.network.unwrap();
+    assert!(buffered_report.startup_latency > 0.0);
+    assert!(buffered_report.stalls >= buffered_report.late_fallbacks);
+    assert!(buffered_report.late_fallbacks > 0.0);
+    assert_eq!(network_report.stalls, 0.0);
+
+    let mut slow = network();
+    slow.bandwidth = 0.0;
+    match builder().network(slow).build() {
+        Err(ConfigError::InvalidNetwork(_)) => {}
+        other => panic!("expected an invalid network, got {:?}", other),
+    }
+}
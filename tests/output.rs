@@ -0,0 +1,113 @@
+extern crate serde_json;
+extern crate simulator;
+
+use simulator::{Energy, Format, NetworkReport, OptimizeVersion, PrefetchReport, RowWriter, SimulationParameters, Size, SocComponents, Stats, SummaryReport};
+
+fn summary(threshold: f64) -> SummaryReport {
+    SummaryReport {
+        video: "Paris".to_string(),
+        users: 57,
+        parameters: SimulationParameters {
+            threshold,
+            segment: 20,
+            level_one: Size::new(1440, 1440),
+            level_two: Size::new(1440, 1440),
+            opt: OptimizeVersion::O0,
+        },
+        wifi_pc: Stats::new(&[252.5]),
+        soc_pc: Stats::new(&[3589.25]),
+        hit_ratios: [Stats::new(&[0.25]), Stats::new(&[0.0]), Stats::new(&[0.75])],
+        segment_hit: Stats::new(&[0.5]),
+        soc_components: SocComponents { decode: 0.0, render: 3000.0, reproject: 589.25 },
+        energy: Energy {
+            duration: 10.0,
+            wifi_watts: 0.25,
+            soc_watts: 3.5,
+            display_watts: 0.5,
+            wifi_joules: 2.5,
+            soc_joules: 35.0,
+            display_joules: 5.0,
+        },
+        network: Some(NetworkReport {
+            bytes: 2e6,
+            active_time: 0.5,
+            tail_time: 1.5,
+            idle_time: 8.0,
+            late_transfers: 1.0,
+            late_fallbacks: 0.0,
+            late_time: 0.25,
+            startup_latency: 0.5,
+            stalls: 2.0,
+            stall_time: 1.25,
+        }),
+        level_bytes: Some([1e6, 5e5, 5e5]),
+        full_bytes: Some(8e6),
+        level_three_height: Some(1440.0),
+        level_three_switches: Some(1.5),
+        prefetch: Some(PrefetchReport { segments: 4.0, wasted_segments: 1.0, wasted_bytes: 2.5e5, wasted_pc: 12.5 }),
+    }
+}
+
+fn write_all(format: Format) -> String {
+    let mut writer = RowWriter::new(format, vec![]);
+    writer.write(&summary(0.8)).unwrap();
+    writer.write(&summary(0.96)).unwrap();
+    writer.finish().unwrap();
+    String::from_utf8(writer.get_mut().clone()).unwrap()
+}
+
+#[test]
+fn test_csv_has_one_header() {
+    let csv = write_all(Format::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("video,users,threshold,segment,l1,l2,opt,"));
+    assert!(lines[2].starts_with("Paris,57,0.96,20,1440x1440,1440x1440,O0,252.5,3589.25,0.25,0,0.75,0.5,0,252.5,"));
+    assert!(lines[2].ends_with(",0,3000,589.25,10,0.25,3.5,0.5,2.5,35,5,2000000,0.5,1.5,8,1,0,0.25,0.5,2,1.25,1000000,500000,500000,8000000,0.75,1440,1.5,4,1,250000,12.5"));
+    assert_eq!(lines[0].split(',').count(), lines[2].split(',').count());
+}
+
+#[test]
+fn test_csv_leaves_models_out_empty() {
+    // a run without network, ladder, policy nor prefetching measured none of them, not 0
+    let mut plain = summary(0.8);
+    plain.network = None;
+    plain.level_bytes = None;
+    plain.full_bytes = None;
+    plain.level_three_height = None;
+    plain.level_three_switches = None;
+    plain.prefetch = None;
+    let mut writer = RowWriter::new(Format::Csv, vec![]);
+    writer.write(&plain).unwrap();
+    let csv = String::from_utf8(writer.get_mut().clone()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert!(lines[1].ends_with(",2.5,35,5,,,,,,,,,,,,,,,,,,,,,"));
+    assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+
+    let json = serde_json::to_value(&plain).unwrap();
+    assert!(json["network"].is_null() && json["prefetch"].is_null());
+}
+
+#[test]
+fn test_jsonl_rows_carry_parameters() {
+    let jsonl = write_all(Format::Jsonl);
+    let rows: Vec<serde_json::Value> = jsonl.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["video"], "Paris");
+    assert_eq!(rows[1]["parameters"]["threshold"], 0.96);
+    assert_eq!(rows[1]["parameters"]["level_two"], "1440x1440");
+    assert_eq!(rows[1]["wifi_pc"]["mean"], 252.5);
+}
+
+#[test]
+fn test_pretty_aligns_columns() {
+    let pretty = write_all(Format::Pretty);
+    let lines: Vec<&str> = pretty.lines().collect();
+    assert_eq!(lines.len(), 3);
+    // every column starts at the same offset in every line
+    let offset = lines[0].find("segment").unwrap();
+    assert_eq!(&lines[1][offset..offset + 2], "20");
+    assert_eq!(&lines[2][offset..offset + 2], "20");
+    assert_eq!("jsonl".parse::<Format>(), Ok(Format::Jsonl));
+    assert!("xml".parse::<Format>().is_err());
+}
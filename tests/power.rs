@@ -0,0 +1,159 @@
+extern crate simulator;
+
+mod common;
+
+use common::{builder, paris, profile, simulate};
+use simulator::{Brightness, ConfigError, DisplayModel, OptimizeVersion, PowerProfile, ScalePoint, Simulator, SimulatorConfig, SimulatorConfigBuilder, SimulatorError, Size, SocScaling, VideoPower};
+
+use std::env;
+use std::fs;
+
+#[test]
+fn test_power_profile_bundle() {
+    let path = env::temp_dir().join(format!("vros-simulate-profiles-{}.json", std::process::id()));
+    fs::write(&path, r#"{
+        "tx2": {
+            "power_360": [{"name": "Paris_WIFI", "value": 326.5}, {"name": "Paris_SOC", "value": 4019.5}],
+            "power_1080p": [{"name": "Paris_WIFI", "value": 27.0}, {"name": "Paris_SOC", "value": 2956.5}]
+        },
+        "phone": {
+            "power_360": [{"name": "Paris_WIFI", "value": 120.0}, {"name": "Paris_SOC", "value": 2000.0}],
+            "power_1080p": [{"name": "Paris_WIFI", "value": 12.0}, {"name": "Paris_SOC", "value": 1500.0},
+                            {"name": "Paris_DECODE", "value": 300.0}]
+        }
+    }"#).unwrap();
+
+    let phone = PowerProfile::from_bundle(&path, "phone").unwrap();
+    assert_eq!(*phone.get_360("Paris").unwrap(), VideoPower { wifi: 120.0, soc: 2000.0, decode: None });
+    assert_eq!(*phone.get_1080p("Paris").unwrap(), VideoPower { wifi: 12.0, soc: 1500.0, decode: Some(300.0) });
+
+    match PowerProfile::from_bundle(&path, "pixel") {
+        Err(SimulatorError::UnknownDevice { device, .. }) => assert_eq!(device, "pixel"),
+        other => panic!("expected an unknown device, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_power_profile_from_files() {
+    let profile = profile();
+    assert!(profile.get_360("Elephant").unwrap().soc > profile.get_1080p("Elephant").unwrap().soc);
+}
+
+#[test]
+fn test_hierarchical_power() {
+    let dataset = paris();
+    let profile = profile();
+    let report = |opt| {
+        let config = SimulatorConfig::builder()
+            .threshold(0.9)
+            .level_two(Size::new(2400, 2160))
+            .opt(opt)
+            .power_profile(profile.clone())
+            .build()
+            .unwrap();
+        Simulator::new(&dataset.video, &dataset.users[0], config).simulate().unwrap()
+    };
+    let o0 = report(OptimizeVersion::O0);
+    let o1 = report(OptimizeVersion::O1);
+    assert!(o0.hit_ratios[1] > 0.0);
+    // the sensor prediction only saves the level two transfer of frames that miss it
+    assert!(o1.wifi_pc < o0.wifi_pc);
+    assert_eq!(o1.soc_pc, o0.soc_pc);
+    // level two costs more than rendering level one and less than the full frame
+    let render = profile.get_1080p("Paris").unwrap().soc;
+    let total = profile.get_360("Paris").unwrap().soc;
+    assert!(o0.soc_pc > render && o0.soc_pc < total);
+}
+
+#[test]
+fn test_soc_scaling_models() {
+    let reference = Size::new(1280, 720);
+    let double = Size::new(2560, 720);
+    assert_eq!(SocScaling::Constant.factor(double), 1.0);
+    assert_eq!(SocScaling::Linear.factor(reference), 1.0);
+    assert_eq!(SocScaling::Linear.factor(double), 2.0);
+
+    let path = env::temp_dir().join(format!("vros-simulate-soc-scaling-{}.json", std::process::id()));
+    fs::write(&path, r#"{"model": "curve", "points": [
+        {"pixels": 1843200, "factor": 1.5}, {"pixels": 921600, "factor": 1.0}
+    ]}"#).unwrap();
+    let points = vec![ScalePoint { pixels: 921600.0, factor: 1.0 }, ScalePoint { pixels: 1843200.0, factor: 1.5 }];
+    let curve = SocScaling::Curve { points: points.clone() };
+    // points are sorted by pixel count when loaded
+    assert_eq!(SocScaling::from_arg(path.to_str().unwrap()).unwrap(), curve);
+    assert_eq!(curve.factor(Size::new(640, 360)), 1.0);
+    assert_eq!(curve.factor(Size::new(1920, 720)), 1.25);
+    assert_eq!(curve.factor(Size::new(3840, 2160)), 1.5);
+
+    let table = SocScaling::Table { points };
+    assert_eq!(table.factor(Size::new(1920, 720)), 1.0);
+    assert_eq!(table.factor(double), 1.5);
+
+    match SimulatorConfig::builder().soc_scaling(SocScaling::Table { points: vec![] }).build() {
+        Err(ConfigError::InvalidSocScaling(_)) => {}
+        other => panic!("expected an invalid soc scaling, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_soc_components() {
+    let dataset = paris();
+    let profile = profile();
+    let report = |reproject_level_two| {
+        let config = SimulatorConfig::builder()
+            .threshold(0.9)
+            .level_two(Size::new(2400, 2160))
+            .reproject_level_two(reproject_level_two)
+            .power_profile(profile.clone())
+            .build()
+            .unwrap();
+        Simulator::new(&dataset.video, &dataset.users[0], config).simulate().unwrap()
+    };
+    let with_l2 = report(true);
+    let components = with_l2.soc_components;
+    assert!((components.total() - with_l2.soc_pc).abs() < 1e-6);
+    // no decode constant in the profile, so decoding is part of rendering
+    assert_eq!(components.decode, 0.0);
+    assert!(components.reproject > 0.0);
+
+    // without level two reprojection only the frames of level three reproject
+    let without_l2 = report(false);
+    assert_eq!(without_l2.soc_components.render, components.render);
+    assert!(without_l2.soc_components.reproject < components.reproject);
+    let reproject = profile.get_360("Paris").unwrap().soc - profile.get_1080p("Paris").unwrap().soc;
+    assert!((without_l2.soc_components.reproject - without_l2.hit_ratios[2] * reproject).abs() < 1e-6);
+}
+
+#[test]
+fn test_display_power() {
+    let path = env::temp_dir().join(format!("vros-simulate-display-{}.json", std::process::id()));
+    fs::write(&path, r#"{
+        "panel": "2000x1000",
+        "refresh_rate": 90,
+        "brightness": [{"from": 10, "level": 0.5}, {"from": 0, "level": 1.0}],
+        "static_power": 100,
+        "pixel_power": 200
+    }"#).unwrap();
+    let display = DisplayModel::load(&path).unwrap();
+    // 100 + 200 * 2 megapixels * 1.5 for 90 Hz, at the brightness of the step
+    assert_eq!(display.power(5.0), 700.0);
+    assert_eq!(display.power(10.0), 400.0);
+
+    let dataset = paris();
+    let builder = builder();
+    let report = |builder: SimulatorConfigBuilder| simulate(&dataset, builder);
+    let without = report(builder.clone());
+    let with = report(builder.clone().display(display.clone()));
+    assert_eq!(without.display_pc, 0.0);
+    assert_eq!((with.wifi_pc, with.soc_pc), (without.wifi_pc, without.soc_pc));
+    // the first 300 frames are at full brightness
+    let bright = 300.0 / with.frame_count as f64;
+    assert!((with.display_pc - (700.0 * bright + 400.0 * (1.0 - bright))).abs() < 1e-9);
+    assert!(with.energy.joules() > without.energy.joules());
+
+    let too_bright = DisplayModel { brightness: Brightness::Constant(1.5), ..display };
+    match builder.display(too_bright).build() {
+        Err(ConfigError::InvalidDisplay(_)) => {}
+        other => panic!("expected an invalid display, got {:?}", other),
+    }
+}
@@ -0,0 +1,106 @@
+extern crate serde_json;
+extern crate simulator;
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::{builder, network, paris};
+use simulator::{AbrModel, BitrateLadder, ConfigError, PlaybackBuffer, Prefetch, PrefetchLevel, Rung, SegmentFetch,
+                SimulatorConfigBuilder, Size, VideoLadder};
+
+// a builder sizing the frames of Paris from 1080p and 4k rungs
+fn sized() -> SimulatorConfigBuilder {
+    let rungs = vec![Rung { size: Size::new(3840, 2160), kbps: 40000.0 }, Rung { size: Size::new(1920, 1080), kbps: 8000.0 }];
+    let mut videos = HashMap::new();
+    videos.insert("Paris".to_string(), VideoLadder { rungs, segments: vec![] });
+    builder().ladder(BitrateLadder { videos })
+}
+
+#[test]
+fn test_prefetch_model() {
+    let prefetch: Prefetch = serde_json::from_str(r#"{"level": "l3", "below": 0.9}"#).unwrap();
+    assert_eq!(prefetch, Prefetch { level: PrefetchLevel::LevelThree, below: 0.9 });
+    assert!(prefetch.prefetches(0.5) && !prefetch.prefetches(0.9));
+
+    match sized().prefetch(Prefetch { level: PrefetchLevel::LevelTwo, below: 1.5 }).build() {
+        Err(ConfigError::InvalidPrefetch(_)) => {}
+        other => panic!("expected an invalid prefetch, got {:?}", other.map(|_| ())),
+    }
+    // without bytes nothing tells what a prefetch wastes
+    match builder().prefetch(prefetch).build() {
+        Err(ConfigError::InvalidPrefetch(_)) => {}
+        other => panic!("expected an invalid prefetch, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_prefetched_playback() {
+    // as in the stalls of the network tests, but the second segment fetches its level three with it
+    let segment = |fallback_bytes: Vec<f64>, prefetched: bool| SegmentFetch { duration: 1.0, bytes: 1e6, fallback_at: 0.5, fallback_bytes, prefetched };
+    let segments = vec![segment(vec![], false), segment(vec![5e5], true), segment(vec![], false)];
+    let playback = network(None).play(&PlaybackBuffer { segments: 2, startup: 0.5 }, &segments, None);
+
+    // the second segment arrives at 2.7s, 0.6s after the first ended, and the third 0.1s late
+    assert_eq!(playback.stalls, 2);
+    assert!((playback.stall_time - 0.7).abs() < 1e-9);
+    assert!((playback.end - 4.8).abs() < 1e-9);
+    let transfers: Vec<(usize, bool, f64)> = playback.transfers.iter().map(|t| (t.0, t.1.fallback, t.1.bytes)).collect();
+    assert_eq!(transfers, vec![(0, false, 1e6), (1, false, 1.5e6), (2, false, 1e6)]);
+    assert_eq!(playback.qualities, vec![None, Some(0), None]);
+
+    // the quality of a prefetched level three is chosen over the whole segment it is sent with,
+    // here 4 and 0.5 MB a second against the 1 MB a second measured on the first segment
+    let abr: AbrModel = serde_json::from_str(r#"{"qualities": ["3840x2160", "1920x1080"],
+        "policy": {"type": "throughput", "safety": 0.9}}"#).unwrap();
+    let prefetched = SegmentFetch { duration: 1.0, bytes: 1e6, fallback_at: 1.0, fallback_bytes: vec![4e6, 5e5], prefetched: true };
+    let segments = vec![segment(vec![], false), prefetched];
+    let playback = network(None).play(&PlaybackBuffer { segments: 2, startup: 0.5 }, &segments, Some(&abr.abr().unwrap()));
+    assert_eq!(playback.qualities, vec![None, Some(1)]);
+}
+
+#[test]
+fn test_prefetch_wastes_wifi() {
+    let dataset = paris();
+    let simulate = |builder: SimulatorConfigBuilder| common::simulate(&dataset, builder);
+    let level_three = |below: f64| Prefetch { level: PrefetchLevel::LevelThree, below };
+    // the wasted bytes cost their share of the wifi power by bytes, with or without a network
+    let wasted_share = |report: &simulator::SimulationReport| {
+        report.wifi_pc * report.prefetch.unwrap().wasted_bytes / report.level_bytes.unwrap().iter().sum::<f64>()
+    };
+    let base = simulate(sized());
+
+    // nothing is less confident than 0
+    let none = simulate(sized().prefetch(level_three(0.0)));
+    assert_eq!((none.wifi_pc, none.prefetch.unwrap().segments), (base.wifi_pc, 0.0));
+    assert_eq!(base.prefetch, None);
+
+    // prefetching does not change what the frames are served from, only what is sent: the bytes of
+    // the frames that needed level three were sent anyway, the others add their cost on top
+    let all = simulate(sized().prefetch(level_three(1.0)));
+    let prefetch = all.prefetch.unwrap();
+    assert!(prefetch.segments > 0.0 && prefetch.segments < all.segment_count as f64);
+    assert!(prefetch.wasted_segments > 0.0 && prefetch.wasted_segments <= prefetch.segments);
+    assert_eq!((all.hit_ratios, all.soc_pc), (base.hit_ratios, base.soc_pc));
+    assert!(prefetch.wasted_bytes > 0.0 && prefetch.wasted_pc > 0.0);
+    assert!((prefetch.wasted_pc - wasted_share(&all)).abs() < 1e-9);
+    assert!((all.wifi_pc - base.wifi_pc - prefetch.wasted_pc).abs() < 1e-9);
+
+    // level two only exists in a hierarchy
+    let level_two = Prefetch { level: PrefetchLevel::LevelTwo, below: 1.0 };
+    match sized().prefetch(level_two).build() {
+        Err(ConfigError::InvalidPrefetch(_)) => {}
+        other => panic!("expected an invalid prefetch, got {:?}", other.map(|_| ())),
+    }
+    let hierarchy = || sized().level_two(Size::new(2160, 1440));
+    let two = simulate(hierarchy().prefetch(level_two));
+    assert!(two.prefetch.unwrap().segments > 0.0);
+    assert!(two.wifi_pc > simulate(hierarchy()).wifi_pc);
+
+    // over a network the radio energy follows the bytes the same way
+    let streamed = simulate(builder().network(network(Some(PlaybackBuffer { segments: 3, startup: 1.0 })))
+        .prefetch(level_three(1.0)));
+    let wasted = streamed.prefetch.unwrap();
+    assert!(wasted.wasted_bytes > 0.0 && wasted.wasted_bytes < streamed.network.unwrap().bytes);
+    assert!((wasted.wasted_pc - wasted_share(&streamed)).abs() < 1e-9);
+}
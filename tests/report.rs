@@ -0,0 +1,65 @@
+extern crate serde_json;
+extern crate simulator;
+
+mod common;
+
+use common::{paris, profile};
+use simulator::{ConfigError, Simulator, SimulatorConfig, Size, Stats};
+
+#[test]
+fn test_report_is_consistent_and_serializable() {
+    let dataset = paris();
+    let config = SimulatorConfig::builder()
+        .threshold(0.9)
+        .level_one(Size::new(1440, 1440))
+        .power_profile(profile())
+        .build()
+        .unwrap();
+    let mut simulator = Simulator::new(&dataset.video, &dataset.users[0], config);
+    let report = simulator.simulate().unwrap();
+
+    assert_eq!(report.video, "Paris");
+    assert!(dataset.users[0].user_file().contains(&report.user));
+    assert_eq!(report.parameters.threshold, 0.9);
+    assert_eq!(report.hit_counts.iter().sum::<usize>(), report.frame_count);
+    assert_eq!(report.hit_ratios[0], report.hit_counts[0] as f64 / report.frame_count as f64);
+    assert!(report.segment_resend_count <= report.segment_count);
+    assert_eq!(report.wifi_pc, simulator.get_wifi_pc());
+    assert_eq!(report.soc_pc, simulator.get_soc_pc());
+    assert_eq!(report.energy.duration, report.frame_count as f64 / 30.0);
+    assert!((report.energy.soc_watts - report.soc_pc / 1000.0).abs() < 1e-12);
+    assert!((report.energy.joules() - (report.wifi_pc + report.soc_pc) / 1000.0 * report.energy.duration).abs() < 1e-9);
+
+    let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["parameters"]["level_one"], "1440x1440");
+    assert_eq!(json["parameters"]["opt"], "O0");
+    assert_eq!(json["hit_counts"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn test_energy_follows_frame_rate() {
+    let mut dataset = paris();
+    let config = SimulatorConfig::builder()
+        .power_profile(profile())
+        .build()
+        .unwrap();
+    let at_30 = Simulator::new(&dataset.video, &dataset.users[0], config.clone()).simulate().unwrap();
+    dataset.video.set_frame_rate(60.0).unwrap();
+    let at_60 = Simulator::new(&dataset.video, &dataset.users[0], config).simulate().unwrap();
+
+    // same average power over half the time
+    assert_eq!(at_60.energy.wifi_watts, at_30.energy.wifi_watts);
+    assert!((at_60.energy.joules() * 2.0 - at_30.energy.joules()).abs() < 1e-9);
+    assert_eq!(dataset.video.set_frame_rate(0.0), Err(ConfigError::InvalidFrameRate(0.0)));
+}
+
+#[test]
+fn test_stats() {
+    let stats = Stats::new(&[4.0, 1.0, 3.0, 2.0, 5.0]);
+    assert_eq!(stats.mean, 3.0);
+    assert_eq!(stats.variance, 2.0);
+    assert_eq!((stats.min, stats.p50, stats.max), (1.0, 3.0, 5.0));
+    assert!((stats.p10 - 1.4).abs() < 1e-12);
+    assert!((stats.p90 - 4.6).abs() < 1e-12);
+    assert!(Stats::new(&[]).mean.is_nan());
+}
@@ -0,0 +1,34 @@
+extern crate simulator;
+
+use simulator::{parallel_map, Dataset, Job, ParameterGrid, PowerProfile, SimulatorConfig, Sweep};
+
+#[test]
+fn test_parallel_map_keeps_order() {
+    let items: Vec<usize> = (0..100).collect();
+    for &workers in &[1, 3, 8, 200] {
+        assert_eq!(parallel_map(&items, workers, |&x| x * x), items.iter().map(|&x| x * x).collect::<Vec<_>>());
+    }
+    assert!(parallel_map(&[] as &[usize], 4, |&x| x).is_empty());
+}
+
+#[test]
+fn test_sweep_is_deterministic() {
+    let datasets = [Dataset::load("Rollercoaster", "user_viewport_result/Rollercoaster-8lsB-P8nGSM",
+                                  "tracedump/roller.txt", "object_as_cluster/roller.json").unwrap()];
+    let grid = ParameterGrid { thresholds: vec![0.8, 1.0], ..ParameterGrid::default() };
+    let base = SimulatorConfig::builder()
+        .power_profile(PowerProfile::from_files("power_4k_360.json", "power_1080p.json").unwrap());
+    let configs = grid.configs(&base).unwrap();
+
+    let sweep = Sweep::new(&datasets, &configs);
+    let jobs = sweep.jobs();
+    let users = datasets[0].users.len();
+    assert_eq!(jobs.len(), 2 * users);
+    assert_eq!(jobs[users], Job { video: 0, config: 1, user: 0 });
+
+    let serial: Vec<_> = sweep.workers(1).run().into_iter().map(|(job, r)| (job, r.unwrap())).collect();
+    let parallel: Vec<_> = Sweep::new(&datasets, &configs).workers(4).run()
+        .into_iter().map(|(job, r)| (job, r.unwrap())).collect();
+    assert_eq!(serial, parallel);
+    assert_eq!(serial.iter().map(|&(job, _)| job).collect::<Vec<_>>(), jobs);
+}
@@ -0,0 +1,37 @@
+extern crate simulator;
+
+mod common;
+
+use common::{paris, profile};
+use simulator::{OptimizeVersion, Simulator, SimulatorConfig, Size};
+
+#[test]
+fn test_timeline_adds_up_to_the_report() {
+    let dataset = paris();
+    let profile = profile();
+    for &opt in &[OptimizeVersion::O0, OptimizeVersion::O1] {
+        let config = SimulatorConfig::builder()
+            .threshold(0.9)
+            .level_two(Size::new(2400, 2160))
+            .opt(opt)
+            .power_profile(profile.clone())
+            .build()
+            .unwrap();
+        let mut simulator = Simulator::new(&dataset.video, &dataset.users[0], config);
+        let report = simulator.simulate().unwrap();
+        let timeline = simulator.timeline().unwrap();
+
+        assert_eq!(timeline.frames.len(), report.frame_count);
+        assert_eq!(timeline.segments.len(), report.segment_count);
+        assert_eq!(timeline.segments.iter().map(|s| s.frames).sum::<usize>(), report.frame_count);
+        let level_two = timeline.frames.iter().filter(|f| f.level == 2).count();
+        assert_eq!(level_two, report.hit_counts[1]);
+
+        // with level two every frame is charged for its own level, so the means match exactly
+        let frames = timeline.frames.len() as f64;
+        let wifi = timeline.frames.iter().map(|f| f.wifi_pc).sum::<f64>() / frames;
+        let soc = timeline.frames.iter().map(|f| f.soc_pc).sum::<f64>() / frames;
+        assert!((wifi - report.wifi_pc).abs() < 1e-6);
+        assert!((soc - report.soc_pc).abs() < 1e-6);
+    }
+}